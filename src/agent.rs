@@ -0,0 +1,490 @@
+use crate::{
+    format::Keyfile,
+    keypair::{Keypair, PublicKey},
+    keyring,
+    result::{anyhow, bail, Result},
+    traits::{
+        txn_sign::{describe_txn, signing_message, TxnSigner},
+        B64,
+    },
+    wallet::Wallet,
+};
+use helium_proto::BlockchainTxn;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    env, fmt, fs,
+    io::{BufRead, BufReader, Write},
+    os::unix::{
+        fs::{DirBuilderExt, MetadataExt, PermissionsExt},
+        net::UnixStream,
+    },
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt},
+    net::UnixListener,
+};
+use zeroize::Zeroizing;
+
+/// Environment variable to override the agent socket with
+pub const AGENT_SOCKET_ENV: &str = "HELIUM_WALLET_AGENT_SOCK";
+
+/// How long the agent waits for a client to send its request or read the
+/// response
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The socket of the agent of the current user. This is the file given by
+/// `HELIUM_WALLET_AGENT_SOCK` or `helium-wallet/agent.sock` in the XDG
+/// runtime directory, falling back to `helium-wallet/agent/agent.sock` in
+/// the XDG config directory. The fallback uses its own directory since the
+/// config directory itself is usually readable by others.
+pub fn socket_path() -> Result<PathBuf> {
+    if let Some(path) = env::var_os(AGENT_SOCKET_ENV) {
+        return Ok(PathBuf::from(path));
+    }
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => {
+            Ok(Path::new(&dir).join("helium-wallet").join("agent.sock"))
+        }
+        _ => Ok(keyring::config_dir()?.join("agent").join("agent.sock")),
+    }
+}
+
+/// A request to the agent. Requests and responses are sent as a single
+/// line of json each.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "request", rename_all = "snake_case")]
+pub enum Request {
+    Status,
+    /// Sign the transaction in the given base64 encoded envelope. The agent
+    /// takes the transaction kind from the envelope and computes the message
+    /// to sign itself, so a client can not pass off one kind of transaction
+    /// as another.
+    Sign {
+        txn: String,
+    },
+    Lock,
+    Unlock {
+        password: String,
+    },
+    Stop,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "response", rename_all = "snake_case")]
+pub enum Response {
+    Status {
+        address: String,
+        locked: bool,
+        idle_timeout: u64,
+        confirm: String,
+    },
+    Signature {
+        signature: String,
+    },
+    Ok,
+    Error {
+        message: String,
+    },
+}
+
+/// Which sign requests the agent asks to confirm before signing
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfirmPolicy {
+    Never,
+    Always,
+    /// Confirm transactions of the given kinds, like `PaymentV2`
+    Kinds(Vec<String>),
+}
+
+impl ConfirmPolicy {
+    pub fn requires_confirmation(&self, kind: &str) -> bool {
+        match self {
+            Self::Never => false,
+            Self::Always => true,
+            Self::Kinds(kinds) => kinds.iter().any(|k| k == kind),
+        }
+    }
+}
+
+impl FromStr for ConfirmPolicy {
+    type Err = crate::result::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "never" => Ok(Self::Never),
+            "always" => Ok(Self::Always),
+            kinds => {
+                let kinds: Vec<String> = kinds
+                    .split(',')
+                    .map(|kind| kind.trim().to_string())
+                    .collect();
+                for kind in &kinds {
+                    if describe_txn(kind, &[]).is_err() {
+                        bail!("Unknown transaction kind {}", kind);
+                    }
+                }
+                Ok(Self::Kinds(kinds))
+            }
+        }
+    }
+}
+
+impl fmt::Display for ConfirmPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Never => f.write_str("never"),
+            Self::Always => f.write_str("always"),
+            Self::Kinds(kinds) => f.write_str(&kinds.join(",")),
+        }
+    }
+}
+
+/// Asks whether a transaction of the given kind and description should be
+/// signed
+pub type Confirm = dyn FnMut(&str, &str) -> bool;
+
+/// The state of a running agent. The agent keeps the encrypted wallet so it
+/// can be unlocked again after it was locked.
+pub struct Agent {
+    wallet: Wallet,
+    keyfile: Option<Keyfile>,
+    keypair: Option<Keypair>,
+    policy: ConfirmPolicy,
+    idle_timeout: Duration,
+    last_used: Instant,
+}
+
+impl Agent {
+    pub fn new(
+        wallet: Wallet,
+        keyfile: Option<Keyfile>,
+        keypair: Keypair,
+        policy: ConfirmPolicy,
+        idle_timeout: Duration,
+    ) -> Self {
+        Self {
+            wallet,
+            keyfile,
+            keypair: Some(keypair),
+            policy,
+            idle_timeout,
+            last_used: Instant::now(),
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.keypair.is_none()
+    }
+
+    /// Drops the decrypted keypair
+    pub fn lock(&mut self) {
+        self.keypair = None;
+    }
+
+    /// When an unlocked agent locks itself if no request arrives
+    pub fn idle_deadline(&self) -> Option<Instant> {
+        self.keypair
+            .as_ref()
+            .map(|_| self.last_used + self.idle_timeout)
+    }
+
+    pub fn handle(&mut self, request: Request, confirm: &mut Confirm) -> Response {
+        match self.try_handle(request, confirm) {
+            Ok(response) => response,
+            Err(err) => Response::Error {
+                message: err.to_string(),
+            },
+        }
+    }
+
+    fn try_handle(&mut self, request: Request, confirm: &mut Confirm) -> Result<Response> {
+        match request {
+            Request::Status => Ok(Response::Status {
+                address: self.wallet.public_key.to_string(),
+                locked: self.is_locked(),
+                idle_timeout: self.idle_timeout.as_secs(),
+                confirm: self.policy.to_string(),
+            }),
+            Request::Sign { txn } => {
+                let keypair = self
+                    .keypair
+                    .as_ref()
+                    .ok_or_else(|| anyhow!("Agent is locked"))?;
+                let (kind, message) = signing_message(&BlockchainTxn::from_b64(&txn)?)?;
+                let description = describe_txn(kind, &message)?;
+                if self.policy.requires_confirmation(kind) && !confirm(kind, &description) {
+                    bail!("Signing {} transaction was denied", kind);
+                }
+                let signature = keypair.sign(&message)?;
+                self.last_used = Instant::now();
+                Ok(Response::Signature {
+                    signature: base64::encode(&signature),
+                })
+            }
+            Request::Lock => {
+                self.lock();
+                Ok(Response::Ok)
+            }
+            Request::Unlock { password } => {
+                let password = Zeroizing::new(password);
+                let keypair = self
+                    .wallet
+                    .decrypt(password.as_bytes(), self.keyfile.as_ref())?;
+                self.keypair = Some(keypair);
+                self.last_used = Instant::now();
+                Ok(Response::Ok)
+            }
+            Request::Stop => {
+                self.lock();
+                Ok(Response::Ok)
+            }
+        }
+    }
+}
+
+/// Serves requests on the given socket until a stop request arrives. Only
+/// connections from processes of the current user are accepted.
+pub async fn serve(mut agent: Agent, path: &Path, confirm: &mut Confirm) -> Result {
+    let listener = bind(path)?;
+    let uid = current_uid();
+    loop {
+        let accepted = match agent.idle_deadline() {
+            Some(deadline) => {
+                let deadline = tokio::time::Instant::from_std(deadline);
+                match tokio::time::timeout_at(deadline, listener.accept()).await {
+                    Ok(accepted) => accepted,
+                    Err(_) => {
+                        agent.lock();
+                        continue;
+                    }
+                }
+            }
+            None => listener.accept().await,
+        };
+        let (stream, _) = accepted?;
+        if stream.peer_cred()?.uid() != uid {
+            continue;
+        }
+        match serve_connection(&mut agent, stream, confirm).await {
+            Ok(true) => break,
+            Ok(false) => (),
+            Err(err) => eprintln!("agent: {}", err),
+        }
+    }
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+/// Handles a single request. Returns whether the agent should stop.
+async fn serve_connection(
+    agent: &mut Agent,
+    stream: tokio::net::UnixStream,
+    confirm: &mut Confirm,
+) -> Result<bool> {
+    let (reader, mut writer) = stream.into_split();
+    let mut line = Zeroizing::new(String::new());
+    // A client that does not send a full request in time would otherwise
+    // block the agent, and with it the idle lock
+    let mut reader = tokio::io::BufReader::new(reader);
+    tokio::time::timeout(CONNECTION_TIMEOUT, reader.read_line(&mut line))
+        .await
+        .map_err(|_| anyhow!("Timed out reading request"))??;
+    let (response, stop) = match serde_json::from_str(&line) {
+        Ok(request) => {
+            let stop = matches!(request, Request::Stop);
+            (agent.handle(request, confirm), stop)
+        }
+        Err(err) => (
+            Response::Error {
+                message: format!("Invalid request: {}", err),
+            },
+            false,
+        ),
+    };
+    let mut response = serde_json::to_string(&response)?;
+    response.push('\n');
+    tokio::time::timeout(CONNECTION_TIMEOUT, writer.write_all(response.as_bytes()))
+        .await
+        .map_err(|_| anyhow!("Timed out writing response"))??;
+    Ok(stop)
+}
+
+/// Binds the agent socket, replacing a stale socket of an agent that is no
+/// longer running
+fn bind(path: &Path) -> Result<UnixListener> {
+    let dir = socket_dir(path)?;
+    if !dir.exists() {
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)?;
+    }
+    check_socket_dir(dir)?;
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            bail!("An agent is already running on {}", path.display());
+        }
+        fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+/// Sends a request to the agent on the given socket. Error responses are
+/// returned as errors.
+pub fn request(path: &Path, request: &Request) -> Result<Response> {
+    check_socket_dir(socket_dir(path)?)?;
+    if fs::metadata(path)?.uid() != current_uid() {
+        bail!("Agent socket {} is not owned by you", path.display());
+    }
+    let mut stream = UnixStream::connect(path)?;
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    match serde_json::from_str(&line)? {
+        Response::Error { message } => bail!("Agent: {}", message),
+        response => Ok(response),
+    }
+}
+
+/// Signs transactions through a running agent
+#[derive(Debug)]
+pub struct AgentClient {
+    path: PathBuf,
+}
+
+impl AgentClient {
+    /// Returns a client for the agent of the current user if one is running
+    /// and holds the given wallet unlocked
+    pub fn for_wallet(public_key: &PublicKey) -> Result<Option<Self>> {
+        let path = socket_path()?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        match request(&path, &Request::Status) {
+            Ok(Response::Status {
+                address, locked, ..
+            }) if !locked && address == public_key.to_string() => Ok(Some(Self { path })),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl TxnSigner for AgentClient {
+    fn sign_txn(&self, envelope: &BlockchainTxn, _msg: &[u8]) -> Result<Vec<u8>> {
+        let sign = Request::Sign {
+            txn: envelope.to_b64()?,
+        };
+        match request(&self.path, &sign)? {
+            Response::Signature { signature } => Ok(base64::decode(&signature)?),
+            _ => bail!("Unexpected response from agent"),
+        }
+    }
+}
+
+fn socket_dir(path: &Path) -> Result<&Path> {
+    path.parent()
+        .ok_or_else(|| anyhow!("Invalid agent socket {}", path.display()))
+}
+
+/// Checks that only the current user can reach sockets in the given
+/// directory
+fn check_socket_dir(dir: &Path) -> Result {
+    let metadata = fs::metadata(dir)?;
+    if metadata.uid() != current_uid() {
+        bail!(
+            "Agent socket directory {} is not owned by you",
+            dir.display()
+        );
+    }
+    if metadata.permissions().mode() & 0o077 != 0 {
+        bail!(
+            "Agent socket directory {} must only be accessible by its owner",
+            dir.display()
+        );
+    }
+    Ok(())
+}
+
+fn current_uid() -> u32 {
+    // getuid can not fail and has no preconditions
+    unsafe { libc::getuid() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn confirm_policy() {
+        assert_eq!(ConfirmPolicy::Never, "never".parse().expect("never"));
+        let kinds: ConfirmPolicy = "PaymentV2, TokenBurnV1".parse().expect("kinds");
+        assert!(kinds.requires_confirmation("PaymentV2"));
+        assert!(!kinds.requires_confirmation("StakeValidatorV1"));
+        assert_eq!("PaymentV2,TokenBurnV1", kinds.to_string());
+        assert!("Payment".parse::<ConfirmPolicy>().is_err());
+        assert!(ConfirmPolicy::Always.requires_confirmation("PaymentV2"));
+    }
+
+    #[test]
+    fn sign_kind_from_envelope() {
+        use crate::{
+            format::Format,
+            pwhash::PwHash,
+            traits::{TxnEnvelope, TxnSign},
+        };
+        use helium_proto::{BlockchainTxnPaymentV2, Payment};
+
+        let keypair = Keypair::default();
+        let wallet = Wallet::encrypt(
+            &keypair,
+            b"password",
+            None,
+            Format::basic(PwHash::pbkdf2(1000)),
+            None,
+        )
+        .expect("wallet creation");
+        let policy: ConfirmPolicy = "PaymentV2".parse().expect("policy");
+        let mut agent = Agent::new(wallet, None, keypair, policy, Duration::from_secs(60));
+        let txn = BlockchainTxnPaymentV2 {
+            payer: agent.wallet.public_key.to_vec(),
+            payments: vec![Payment {
+                payee: Keypair::default().public_key().to_vec(),
+                amount: 1,
+                memo: 0,
+            }],
+            fee: 0,
+            nonce: 1,
+            signature: vec![],
+        };
+        let sign = || Request::Sign {
+            txn: txn.in_envelope().to_b64().expect("envelope"),
+        };
+
+        let mut confirmed = vec![];
+        let mut deny = |kind: &str, _: &str| {
+            confirmed.push(kind.to_string());
+            false
+        };
+        assert!(matches!(
+            agent.handle(sign(), &mut deny),
+            Response::Error { .. }
+        ));
+        assert_eq!(vec!["PaymentV2".to_string()], confirmed);
+
+        let mut allow = |_: &str, _: &str| true;
+        match agent.handle(sign(), &mut allow) {
+            Response::Signature { signature } => {
+                let signature = base64::decode(&signature).expect("signature");
+                assert!(txn.verify(&agent.wallet.public_key, &signature).is_ok());
+            }
+            response => panic!("unexpected response {:?}", response),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{
+    agent::{self, socket_path, Agent, ConfirmPolicy, Request, Response},
+    cmd::*,
+    result::{bail, Result},
+};
+use prettytable::Table;
+use serde_json::json;
+use std::time::Duration;
+
+#[derive(Debug, StructOpt)]
+/// Run a wallet agent that holds an unlocked wallet.
+///
+/// The agent decrypts the wallet once and signs transactions for other
+/// commands over a Unix socket, so they do not ask for the password or
+/// decrypt the wallet themselves. The socket is `helium-wallet/agent.sock`
+/// in the XDG runtime directory, or the file given by
+/// HELIUM_WALLET_AGENT_SOCK, and only accepts connections from the current
+/// user.
+pub enum Cmd {
+    Start(Start),
+    Status(Status),
+    Lock(Lock),
+    Unlock(Unlock),
+    Stop(Stop),
+}
+
+#[derive(Debug, StructOpt)]
+/// Unlock the wallet and serve sign requests until stopped. The agent runs
+/// in the foreground and asks for confirmations on its terminal.
+pub struct Start {
+    /// Lock the wallet after this many seconds without sign requests
+    #[structopt(long, default_value = "900")]
+    idle_timeout: u64,
+
+    /// Which transactions to confirm before signing: "never", "always" or a
+    /// comma separated list of transaction kinds, like
+    /// "PaymentV2,TokenBurnV1"
+    #[structopt(long, default_value = "never")]
+    confirm: ConfirmPolicy,
+}
+
+#[derive(Debug, StructOpt)]
+/// Show the wallet and state of the running agent
+pub struct Status {}
+
+#[derive(Debug, StructOpt)]
+/// Lock the running agent. The wallet stays loaded but sign requests are
+/// refused until it is unlocked again.
+pub struct Lock {}
+
+#[derive(Debug, StructOpt)]
+/// Unlock the running agent with the wallet password
+pub struct Unlock {}
+
+#[derive(Debug, StructOpt)]
+/// Stop the running agent
+pub struct Stop {}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::Start(cmd) => cmd.run(opts).await,
+            Cmd::Status(cmd) => cmd.run(opts).await,
+            Cmd::Lock(cmd) => cmd.run(opts).await,
+            Cmd::Unlock(cmd) => cmd.run(opts).await,
+            Cmd::Stop(cmd) => cmd.run(opts).await,
+        }
+    }
+}
+
+impl Start {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let password = get_password(&opts.password, false)?;
+        let keyfile = opts.keyfile()?;
+        let wallet = load_wallet(opts.files)?;
+        let keypair = wallet.decrypt(password.as_bytes(), keyfile.as_ref())?;
+
+        let path = socket_path()?;
+        eprintln!(
+            "Agent for {} listening on {}",
+            wallet.public_key,
+            path.display()
+        );
+        let agent = Agent::new(
+            wallet.into_wallet(),
+            keyfile,
+            keypair,
+            self.confirm.clone(),
+            Duration::from_secs(self.idle_timeout),
+        );
+        agent::serve(agent, &path, &mut confirm_sign).await
+    }
+}
+
+impl Status {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let path = socket_path()?;
+        let response = agent::request(&path, &Request::Status)?;
+        if let Response::Status {
+            address,
+            locked,
+            idle_timeout,
+            confirm,
+        } = response
+        {
+            match opts.format {
+                OutputFormat::Table => {
+                    let mut table = Table::new();
+                    table.add_row(row!["Key", "Value"]);
+                    table.add_row(row!["Socket", path.display()]);
+                    table.add_row(row!["Address", address]);
+                    table.add_row(row!["Locked", locked]);
+                    table.add_row(row!["Idle Timeout", idle_timeout]);
+                    table.add_row(row!["Confirm", confirm]);
+                    print_table(&table)
+                }
+                OutputFormat::Json => {
+                    let table = json!({
+                        "socket": path.display().to_string(),
+                        "address": address,
+                        "locked": locked,
+                        "idle_timeout": idle_timeout,
+                        "confirm": confirm,
+                    });
+                    print_json(&table)
+                }
+            }
+        } else {
+            bail!("Unexpected response from agent")
+        }
+    }
+}
+
+impl Lock {
+    pub async fn run(&self, _opts: Opts) -> Result {
+        agent::request(&socket_path()?, &Request::Lock)?;
+        Ok(())
+    }
+}
+
+impl Unlock {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let password = get_password(&opts.password, false)?;
+        let unlock = Request::Unlock {
+            password: password.to_string(),
+        };
+        agent::request(&socket_path()?, &unlock)?;
+        Ok(())
+    }
+}
+
+impl Stop {
+    pub async fn run(&self, _opts: Opts) -> Result {
+        agent::request(&socket_path()?, &Request::Stop)?;
+        Ok(())
+    }
+}
+
+/// Asks on the terminal of the agent whether to sign a transaction
+fn confirm_sign(kind: &str, description: &str) -> bool {
+    eprintln!("Sign request for {} transaction:\n{}", kind, description);
+    dialoguer::Confirm::new()
+        .with_prompt("Sign this transaction?")
+        .default(false)
+        .interact()
+        .unwrap_or(false)
+}
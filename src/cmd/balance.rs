@@ -12,20 +12,20 @@ use serde_json::json;
 /// a precision of 8 decimals.
 pub struct Cmd {
     /// Addresses to get balances for
-    #[structopt(short = "a", long = "address")]
+    #[structopt(short = "a", long = "address", parse(try_from_str = parse_address))]
     addresses: Vec<PublicKey>,
 }
 
 impl Cmd {
     pub async fn run(&self, opts: Opts) -> Result {
         let addresses = collect_addresses(opts.files, self.addresses.clone())?;
-        let api_url = api_url(
+        let client = api_client(
+            &opts.config,
             addresses
                 .first()
                 .map(|key| key.network)
                 .ok_or_else(|| anyhow!("at least one address expected"))?,
         );
-        let client = Client::new_with_base_url(api_url);
 
         let mut results = Vec::with_capacity(self.addresses.len());
         for address in addresses {
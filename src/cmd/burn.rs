@@ -12,7 +12,7 @@ use serde_json::json;
 /// Burn HNT to Data Credits (DC) from this wallet to given payees wallet.
 pub struct Cmd {
     /// Account address to send the resulting DC to.
-    #[structopt(long)]
+    #[structopt(long, parse(try_from_str = parse_address))]
     payee: PublicKey,
 
     /// Memo field to include. Provide as a base64 encoded string
@@ -30,25 +30,25 @@ pub struct Cmd {
 
 impl Cmd {
     pub async fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
 
-        let client = Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = api_client(&opts.config, wallet.public_key.network);
 
-        let keypair = wallet.decrypt(password.as_bytes())?;
-        let account = accounts::get(&client, &keypair.public_key().to_string()).await?;
+        let signer = wallet_signer(&wallet, &opts.password, keyfile.as_ref())?;
+        let account = accounts::get(&client, &signer.public_key().to_string()).await?;
 
         let mut txn = BlockchainTxnTokenBurnV1 {
             fee: 0,
             payee: self.payee.to_bytes().to_vec(),
             amount: u64::from(self.amount),
-            payer: keypair.public_key().into(),
+            payer: signer.public_key().into(),
             memo: u64::from(&self.memo),
             nonce: account.speculative_nonce + 1,
             signature: Vec::new(),
         };
         txn.fee = txn.txn_fee(&get_txn_fees(&client).await?)?;
-        txn.signature = txn.sign(&keypair)?;
+        txn.signature = txn.sign(&signer)?;
 
         let envelope = txn.in_envelope();
         let status = maybe_submit_txn(self.commit, &client, &envelope).await?;
@@ -64,9 +64,11 @@ fn print_txn(
 ) -> Result {
     match format {
         OutputFormat::Table => {
+            let contacts = address_book();
+            let payee = PublicKey::from_bytes(&txn.payee)?;
             ptable!(
                 ["Key", "Value"],
-                ["Payee", PublicKey::from_bytes(&txn.payee)?.to_string()],
+                ["Payee", contacts.display(&payee)],
                 ["Memo", Memo::from(txn.memo).to_string()],
                 ["Amount (HNT)", Hnt::from(txn.amount)],
                 ["Fee (DC)", txn.fee],
@@ -15,10 +15,10 @@ pub struct Cmd {
 
 impl Cmd {
     pub async fn run(&self, opts: Opts) -> Result {
-        let envelope = read_txn(&self.txn)?;
+        let envelope = read_txn(&self.txn, &opts.password)?;
 
         let wallet = load_wallet(opts.files)?;
-        let client = helium_api::Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = api_client(&opts.config, wallet.public_key.network);
 
         let status = maybe_submit_txn(true, &client, &envelope).await?;
         print_txn(&envelope, &status, opts.format)
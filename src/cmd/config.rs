@@ -0,0 +1,94 @@
+use crate::{cmd::*, keypair::Network, keyring::Keyring, result::Result};
+use prettytable::Table;
+use serde_json::json;
+
+#[derive(Debug, StructOpt)]
+/// Inspect the wallet configuration.
+///
+/// Settings are read from `helium-wallet/config.toml` in the XDG config
+/// directory, or the file given by HELIUM_WALLET_CONFIG, and from the
+/// nearest `.helium-wallet.toml` in the current directory or its parents.
+/// Project settings override global ones, environment variables override
+/// both and command line options override everything. The `api` and
+/// `password` settings are only read from the global config file.
+pub enum Cmd {
+    Show(Show),
+}
+
+#[derive(Debug, StructOpt)]
+/// Show the effective settings and where they were read from
+pub struct Show {}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::Show(cmd) => cmd.run(opts).await,
+        }
+    }
+}
+
+impl Show {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let config = &opts.config;
+        let files: Vec<String> = config
+            .files
+            .iter()
+            .map(|file| file.display().to_string())
+            .collect();
+        let ignored: Vec<String> = config
+            .ignored
+            .iter()
+            .map(|(name, file)| format!("{} in {}", name, file.display()))
+            .collect();
+        let wallet = match (&opts.wallet, &config.wallet) {
+            (Some(name), _) | (None, Some(name)) => Some(name.clone()),
+            (None, None) => Keyring::open()
+                .ok()
+                .map(|keyring| keyring.default_name())
+                .transpose()?
+                .flatten(),
+        };
+        let password = opts.password.to_string();
+        let format = match opts.format {
+            OutputFormat::Table => "table",
+            OutputFormat::Json => "json",
+        };
+        let mainnet = api_url(config, Network::MainNet);
+        let testnet = api_url(config, Network::TestNet);
+        let staking = staking_url(config);
+        match opts.format {
+            OutputFormat::Table => {
+                let mut table = Table::new();
+                table.add_row(row!["Key", "Value"]);
+                table.add_row(row!["Config files", files.join("\n")]);
+                table.add_row(row!["Mainnet API", mainnet]);
+                table.add_row(row!["Testnet API", testnet]);
+                table.add_row(row!["Staking API", staking]);
+                table.add_row(row!["Timeout", config.timeout()]);
+                table.add_row(row!["Format", format]);
+                table.add_row(row!["Wallet", wallet.unwrap_or_default()]);
+                table.add_row(row!["Password", password]);
+                if !ignored.is_empty() {
+                    table.add_row(row!["Ignored", ignored.join("\n")]);
+                }
+                print_table(&table)
+            }
+            OutputFormat::Json => {
+                let table = json!({
+                    "files": files,
+                    "api": {
+                        "mainnet": mainnet,
+                        "testnet": testnet,
+                        "staking": staking,
+                        "timeout": config.timeout(),
+                    },
+                    "format": format,
+                    "wallet": wallet,
+                    "password": password,
+                    "ignored": ignored,
+                });
+                print_json(&table)
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{cmd::*, contacts::AddressBook, keypair::PublicKey, result::Result};
+use prettytable::Table;
+use serde_json::json;
+
+#[derive(Debug, StructOpt)]
+/// Manage the local address book.
+///
+/// Contacts can be used as @alias wherever an address is expected, and
+/// are shown next to their addresses in transaction output.
+pub enum Cmd {
+    List(List),
+    Add(Add),
+    Remove(Remove),
+}
+
+#[derive(Debug, StructOpt)]
+/// List all contacts
+pub struct List {}
+
+#[derive(Debug, StructOpt)]
+/// Add a contact
+pub struct Add {
+    /// Alias for the contact, used as @alias
+    alias: String,
+
+    /// Address of the contact
+    #[structopt(parse(try_from_str = parse_address))]
+    address: PublicKey,
+}
+
+#[derive(Debug, StructOpt)]
+/// Remove a contact
+pub struct Remove {
+    /// Alias of the contact to remove
+    alias: String,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::List(cmd) => cmd.run(opts).await,
+            Cmd::Add(cmd) => cmd.run(opts).await,
+            Cmd::Remove(cmd) => cmd.run(opts).await,
+        }
+    }
+}
+
+impl List {
+    pub async fn run(&self, opts: Opts) -> Result {
+        print_contacts(&AddressBook::open()?, opts.format)
+    }
+}
+
+impl Add {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let mut contacts = AddressBook::open()?;
+        let alias = self.alias.trim_start_matches('@');
+        if let Some(existing) = contacts.alias(&self.address) {
+            eprintln!("Note: address is already known as @{}", existing);
+        }
+        contacts.add(alias, self.address.clone())?;
+        contacts.save()?;
+        print_contacts(&contacts, opts.format)
+    }
+}
+
+impl Remove {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let mut contacts = AddressBook::open()?;
+        contacts.remove(self.alias.trim_start_matches('@'))?;
+        contacts.save()?;
+        print_contacts(&contacts, opts.format)
+    }
+}
+
+fn print_contacts(contacts: &AddressBook, format: OutputFormat) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.add_row(row!["Alias", "Address"]);
+            for (alias, address) in contacts.iter() {
+                table.add_row(row![format!("@{}", alias), address]);
+            }
+            print_table(&table)
+        }
+        OutputFormat::Json => {
+            let contacts: Vec<serde_json::Value> = contacts
+                .iter()
+                .map(|(alias, address)| {
+                    json!({
+                        "alias": alias,
+                        "address": address.to_string(),
+                    })
+                })
+                .collect();
+            print_json(&contacts)
+        }
+    }
+}
@@ -1,22 +1,37 @@
 use crate::{
-    cmd::*,
+    cmd::{
+        hd::{derive_keypair, HdOpts},
+        pwhash::PwHashOpts,
+        *,
+    },
     format::{self, Format},
-    keypair::{KeyTag, KeyType, Keypair, Network, KEYTYPE_ED25519_STR, NETTYPE_MAIN_STR},
-    mnemonic::{mnemonic_to_entropy, SeedType},
-    pwhash::PwHash,
+    hd::DerivationPath,
+    keypair::{
+        KeyTag, KeyType, Keypair, Network, KEYTYPE_ED25519_STR, NETTYPE_MAIN_STR, PUBLIC_KEY_LENGTH,
+    },
+    mnemonic::{entropy_to_mnemonic, mnemonic_to_entropy, mnemonic_to_seed, Language, SeedType},
     result::Result,
     wallet::Wallet,
 };
+use sodiumoxide::randombytes;
 use std::{
     fs, io,
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
 };
+use unicode_normalization::UnicodeNormalization;
 
 #[derive(Debug, StructOpt)]
 /// Create a new wallet
 pub enum Cmd {
     Basic(Basic),
     Sharded(Sharded),
+    Vanity(Vanity),
 }
 
 #[derive(Debug, StructOpt)]
@@ -30,10 +45,33 @@ pub struct Basic {
     /// Overwrite an existing file
     force: bool,
 
+    #[structopt(flatten)]
+    pwhash: PwHashOpts,
+
+    #[structopt(flatten)]
+    metadata: MetadataOpts,
+
     #[structopt(long, possible_values = &["bip39", "mobile"], case_insensitive = true)]
     /// Use a BIP39 or mobile app seed phrase to generate the wallet keys
     seed: Option<SeedType>,
 
+    #[structopt(long, conflicts_with = "seed")]
+    /// Generate a new BIP39 seed phrase for the wallet keys and display it
+    /// for backup
+    new_seed: bool,
+
+    #[structopt(long, possible_values = &["12", "24"], default_value = "24")]
+    /// The number of words in a newly generated seed phrase
+    seed_words: usize,
+
+    #[structopt(long, possible_values = &Language::variants(), case_insensitive = true)]
+    /// The language of the seed phrase. A given seed phrase's language is
+    /// detected from the words and new seed phrases are English if not given
+    seed_language: Option<Language>,
+
+    #[structopt(flatten)]
+    hd: HdOpts,
+
     #[structopt(long, default_value = NETTYPE_MAIN_STR)]
     /// The network to generate the wallet (testnet/mainnet)
     network: Network,
@@ -54,6 +92,12 @@ pub struct Sharded {
     /// Overwrite an existing file
     force: bool,
 
+    #[structopt(flatten)]
+    pwhash: PwHashOpts,
+
+    #[structopt(flatten)]
+    metadata: MetadataOpts,
+
     #[structopt(short = "n", long = "shards", default_value = "5")]
     /// Number of shards to break the key into
     key_share_count: u8,
@@ -66,6 +110,61 @@ pub struct Sharded {
     /// Use a BIP39 or mobile app seed phrase to generate the wallet keys
     seed: Option<SeedType>,
 
+    #[structopt(long, conflicts_with = "seed")]
+    /// Generate a new BIP39 seed phrase for the wallet keys and display it
+    /// for backup
+    new_seed: bool,
+
+    #[structopt(long, possible_values = &["12", "24"], default_value = "24")]
+    /// The number of words in a newly generated seed phrase
+    seed_words: usize,
+
+    #[structopt(long, possible_values = &Language::variants(), case_insensitive = true)]
+    /// The language of the seed phrase. A given seed phrase's language is
+    /// detected from the words and new seed phrases are English if not given
+    seed_language: Option<Language>,
+
+    #[structopt(flatten)]
+    hd: HdOpts,
+
+    #[structopt(long, default_value = NETTYPE_MAIN_STR)]
+    /// The network to generate the wallet (testnet/mainnet)
+    network: Network,
+
+    #[structopt(long, default_value = KEYTYPE_ED25519_STR)]
+    /// The type of key to generate (ecc_compact/ed25519)
+    key_type: KeyType,
+}
+
+#[derive(Debug, StructOpt)]
+/// Create a new basic wallet with an address that starts with a given prefix.
+/// Keys are generated on all CPU cores until one matches, which can take a
+/// very long time for longer prefixes.
+pub struct Vanity {
+    #[structopt(long)]
+    /// The address prefix to search for. Helium addresses only contain
+    /// base58 characters and start with characters fixed by the key type
+    /// and network, "13" or "14" for most mainnet ed25519 keys.
+    prefix: String,
+
+    #[structopt(long)]
+    /// Match the prefix regardless of upper or lower case
+    ignore_case: bool,
+
+    #[structopt(short, long, default_value = "wallet.key")]
+    /// Output file to store the key in
+    output: PathBuf,
+
+    #[structopt(long)]
+    /// Overwrite an existing file
+    force: bool,
+
+    #[structopt(flatten)]
+    pwhash: PwHashOpts,
+
+    #[structopt(flatten)]
+    metadata: MetadataOpts,
+
     #[structopt(long, default_value = NETTYPE_MAIN_STR)]
     /// The network to generate the wallet (testnet/mainnet)
     network: Network,
@@ -80,26 +179,47 @@ impl Cmd {
         match self {
             Cmd::Basic(cmd) => cmd.run(opts).await,
             Cmd::Sharded(cmd) => cmd.run(opts).await,
+            Cmd::Vanity(cmd) => cmd.run(opts).await,
         }
     }
 }
 
 impl Basic {
     pub async fn run(&self, opts: Opts) -> Result {
-        let seed_words = match &self.seed {
-            Some(seed_type) => Some(get_seed_words(seed_type)?),
-            None => None,
-        };
-        let password = get_password(true)?;
+        let derivation_path = self.hd.derivation_path()?;
+        let (seed_words, seed_type) = get_seed(
+            &self.seed,
+            self.new_seed,
+            self.seed_words,
+            self.seed_language,
+            derivation_path.is_some(),
+        )?;
+        let passphrase = self.hd.passphrase()?;
+        let password = get_password(&opts.password, true)?;
+        let keyfile = opts.keyfile()?;
         let tag = KeyTag {
             network: self.network,
             key_type: self.key_type,
         };
-        let keypair = gen_keypair(tag, seed_words, self.seed.as_ref())?;
+        let metadata = self.metadata.new_metadata(derivation_path.clone());
+        let keypair = gen_keypair(
+            tag,
+            seed_words,
+            seed_type,
+            self.seed_language,
+            derivation_path,
+            &passphrase,
+        )?;
         let format = format::Basic {
-            pwhash: PwHash::argon2id13_default(),
+            pwhash: self.pwhash.pwhash()?,
         };
-        let wallet = Wallet::encrypt(&keypair, password.as_bytes(), Format::Basic(format))?;
+        let wallet = Wallet::encrypt(
+            &keypair,
+            password.as_bytes(),
+            keyfile.as_ref(),
+            Format::Basic(format),
+            metadata,
+        )?;
         let mut writer = open_output_file(&self.output, !self.force)?;
         wallet.write(&mut writer)?;
         verify::print_result(&wallet, true, opts.format)
@@ -108,50 +228,445 @@ impl Basic {
 
 impl Sharded {
     pub async fn run(&self, opts: Opts) -> Result {
-        let seed_words = match &self.seed {
-            Some(seed_type) => Some(get_seed_words(seed_type)?),
-            None => None,
-        };
-        let password = get_password(true)?;
+        let derivation_path = self.hd.derivation_path()?;
+        let (seed_words, seed_type) = get_seed(
+            &self.seed,
+            self.new_seed,
+            self.seed_words,
+            self.seed_language,
+            derivation_path.is_some(),
+        )?;
+        let passphrase = self.hd.passphrase()?;
+        let password = get_password(&opts.password, true)?;
+        let keyfile = opts.keyfile()?;
         let tag = KeyTag {
             network: self.network,
             key_type: self.key_type,
         };
 
-        let keypair = gen_keypair(tag, seed_words, self.seed.as_ref())?;
+        let metadata = self.metadata.new_metadata(derivation_path.clone());
+        let keypair = gen_keypair(
+            tag,
+            seed_words,
+            seed_type,
+            self.seed_language,
+            derivation_path,
+            &passphrase,
+        )?;
         let format = format::Sharded {
             key_share_count: self.key_share_count,
             recovery_threshold: self.recovery_threshold,
-            pwhash: PwHash::argon2id13_default(),
+            pwhash: self.pwhash.pwhash()?,
             key_shares: vec![],
         };
-        let wallet = Wallet::encrypt(&keypair, password.as_bytes(), Format::Sharded(format))?;
-
-        let extension = get_file_extension(&self.output);
-        for (i, shard) in wallet.shards()?.iter().enumerate() {
-            let mut filename = self.output.clone();
-            let share_extension = format!("{}.{}", extension, (i + 1).to_string());
-            filename.set_extension(share_extension);
-            let mut writer = open_output_file(&filename, !self.force)?;
+        let wallet = Wallet::encrypt(
+            &keypair,
+            password.as_bytes(),
+            keyfile.as_ref(),
+            Format::Sharded(format),
+            metadata,
+        )?;
+
+        let filenames = shard_filenames(&self.output, self.key_share_count);
+        for (filename, shard) in filenames.iter().zip(wallet.shards()?) {
+            let mut writer = open_output_file(filename, !self.force)?;
             shard.write(&mut writer)?;
         }
         verify::print_result(&wallet, true, opts.format)
     }
 }
 
+impl Vanity {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let tag = KeyTag {
+            network: self.network,
+            key_type: self.key_type,
+        };
+        let prefix = VanityPrefix::new(&self.prefix, self.ignore_case, tag)?;
+        let password = get_password(&opts.password, true)?;
+        let keyfile = opts.keyfile()?;
+        let keypair = find_vanity_keypair(tag, prefix)?;
+        let metadata = self.metadata.new_metadata(None);
+        let format = format::Basic {
+            pwhash: self.pwhash.pwhash()?,
+        };
+        let wallet = Wallet::encrypt(
+            &keypair,
+            password.as_bytes(),
+            keyfile.as_ref(),
+            Format::Basic(format),
+            metadata,
+        )?;
+        let mut writer = open_output_file(&self.output, !self.force)?;
+        wallet.write(&mut writer)?;
+        verify::print_result(&wallet, true, opts.format)
+    }
+}
+
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// An address prefix to search for
+struct VanityPrefix {
+    prefix: String,
+    ignore_case: bool,
+    ranges: Vec<AddressRange>,
+}
+
+/// The smallest and largest address of a given length that keys of a key
+/// tag can have. Base58 characters are in ASCII order, so addresses of the
+/// same length compare like the numbers they encode.
+struct AddressRange {
+    min: Vec<char>,
+    max: Vec<char>,
+}
+
+impl VanityPrefix {
+    fn new(prefix: &str, ignore_case: bool, tag: KeyTag) -> Result<Self> {
+        if prefix.is_empty() {
+            bail!("The vanity prefix can not be empty");
+        }
+        if let Some(c) = prefix.chars().find(|c| !Self::is_base58(*c, ignore_case)) {
+            bail!(
+                "Prefix character '{}' can not appear in a base58 address",
+                c
+            );
+        }
+        let ranges = AddressRange::for_tag(tag);
+        let prefix = Self {
+            prefix: prefix.to_string(),
+            ignore_case,
+            ranges,
+        };
+        if !prefix.is_reachable() {
+            let first = &prefix.ranges[0];
+            let last = &prefix.ranges[prefix.ranges.len() - 1];
+            bail!(
+                "No {} {} address can start with \"{}\", addresses range from \"{}...\" to \"{}...\"",
+                tag.network,
+                tag.key_type,
+                prefix.prefix,
+                first.min.iter().take(4).collect::<String>(),
+                last.max.iter().take(4).collect::<String>(),
+            );
+        }
+        Ok(prefix)
+    }
+
+    fn is_base58(c: char, ignore_case: bool) -> bool {
+        if ignore_case {
+            BASE58_ALPHABET.contains(c.to_ascii_lowercase())
+                || BASE58_ALPHABET.contains(c.to_ascii_uppercase())
+        } else {
+            BASE58_ALPHABET.contains(c)
+        }
+    }
+
+    /// The base58 characters the prefix character at each position matches
+    fn candidates(&self) -> Vec<Vec<char>> {
+        self.prefix
+            .chars()
+            .map(|c| {
+                BASE58_ALPHABET
+                    .chars()
+                    .filter(|a| {
+                        if self.ignore_case {
+                            a.eq_ignore_ascii_case(&c)
+                        } else {
+                            *a == c
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn is_reachable(&self) -> bool {
+        let candidates = self.candidates();
+        self.ranges
+            .iter()
+            .any(|range| range.contains_prefix(&candidates, 0, true, true))
+    }
+
+    fn matches(&self, address: &str) -> bool {
+        match address.get(..self.prefix.len()) {
+            Some(start) if self.ignore_case => start.eq_ignore_ascii_case(&self.prefix),
+            Some(start) => start == self.prefix,
+            None => false,
+        }
+    }
+
+    /// A rough estimate of the number of keys to generate to find a match.
+    /// Leading characters that are the same for all addresses of the key
+    /// tag are skipped. The first character that varies can only take the
+    /// values between the smallest and largest address, the characters
+    /// after it are assumed to be uniformly distributed.
+    fn expected_attempts(&self) -> f64 {
+        let longest = &self.ranges[self.ranges.len() - 1];
+        let fixed = (0..longest.max.len())
+            .take_while(|i| {
+                let c = longest.max[*i];
+                self.ranges
+                    .iter()
+                    .all(|range| range.min.get(*i) == Some(&c) && range.max.get(*i) == Some(&c))
+            })
+            .count();
+        self.candidates()
+            .iter()
+            .enumerate()
+            .skip(fixed)
+            .map(|(i, matching)| {
+                let choices = if i == fixed {
+                    BASE58_ALPHABET
+                        .chars()
+                        .filter(|c| *c >= longest.min[i] && *c <= longest.max[i])
+                        .count()
+                } else {
+                    58
+                };
+                choices as f64 / matching.len().max(1) as f64
+            })
+            .product()
+    }
+}
+
+impl AddressRange {
+    /// Returns the address ranges for each address length that keys of the
+    /// given tag can have. An address is the base58check encoding of a
+    /// zero version byte, the tag byte, the public key and a four byte
+    /// checksum. For the zero mainnet ecc_compact tag, keys whose first
+    /// byte is zero are left out since they only add yet another leading
+    /// "1".
+    fn for_tag(tag: KeyTag) -> Vec<Self> {
+        let network = match tag.network {
+            Network::MainNet => 0x00,
+            Network::TestNet => 0x10,
+        };
+        let key_type = match tag.key_type {
+            KeyType::EccCompact => 0x00,
+            KeyType::Ed25519 => 0x01,
+        };
+        let tag_byte: u8 = network | key_type;
+        let key_len = PUBLIC_KEY_LENGTH - 1;
+        let mut min = vec![0x00, tag_byte];
+        min.resize(2 + key_len + 4, 0x00);
+        if tag_byte == 0x00 {
+            min[2] = 0x01;
+        }
+        let mut max = vec![0x00, tag_byte];
+        max.resize(2 + key_len + 4, 0xff);
+
+        // Leading zero bytes encode as leading "1"s, the rest as a number
+        let leading = if tag_byte == 0x00 { 2 } else { 1 };
+        let encode = |bytes: &[u8]| -> Vec<char> {
+            bs58::encode(&bytes[leading..])
+                .into_string()
+                .chars()
+                .collect()
+        };
+        let (min, max) = (encode(&min), encode(&max));
+        let ones = vec!['1'; leading];
+        (min.len()..=max.len())
+            .map(|len| {
+                let lower = if len == min.len() {
+                    min.clone()
+                } else {
+                    let mut lower = vec!['1'; len];
+                    lower[0] = '2';
+                    lower
+                };
+                let upper = if len == max.len() {
+                    max.clone()
+                } else {
+                    vec!['z'; len]
+                };
+                Self {
+                    min: [ones.as_slice(), &lower].concat(),
+                    max: [ones.as_slice(), &upper].concat(),
+                }
+            })
+            .collect()
+    }
+
+    /// Whether an address in this range starts with one of the candidate
+    /// characters at each position. `low` and `high` track whether the
+    /// characters chosen so far are equal to the start of the smallest and
+    /// largest address.
+    fn contains_prefix(&self, candidates: &[Vec<char>], pos: usize, low: bool, high: bool) -> bool {
+        if pos == candidates.len() {
+            return true;
+        }
+        if pos >= self.min.len() {
+            return false;
+        }
+        candidates[pos].iter().any(|c| {
+            !(low && *c < self.min[pos])
+                && !(high && *c > self.max[pos])
+                && self.contains_prefix(
+                    candidates,
+                    pos + 1,
+                    low && *c == self.min[pos],
+                    high && *c == self.max[pos],
+                )
+        })
+    }
+}
+
+/// Generates keypairs on all CPU cores until the address of one matches the
+/// given prefix. Progress is shown on stderr.
+fn find_vanity_keypair(tag: KeyTag, prefix: VanityPrefix) -> Result<Keypair> {
+    let prefix = Arc::new(prefix);
+    let found = Arc::new(AtomicBool::new(false));
+    let attempts = Arc::new(AtomicU64::new(0));
+    let (sender, receiver) = mpsc::channel();
+    let workers: Vec<_> = (0..num_cpus::get())
+        .map(|_| {
+            let (prefix, found, attempts, sender) = (
+                prefix.clone(),
+                found.clone(),
+                attempts.clone(),
+                sender.clone(),
+            );
+            thread::spawn(move || {
+                while !found.load(Ordering::Relaxed) {
+                    let keypair = Keypair::generate(tag);
+                    attempts.fetch_add(1, Ordering::Relaxed);
+                    if prefix.matches(&keypair.public_key().to_string())
+                        && !found.swap(true, Ordering::Relaxed)
+                    {
+                        let _ = sender.send(keypair);
+                    }
+                }
+            })
+        })
+        .collect();
+    // Only the workers hold senders now, so the search stops with an error
+    // instead of hanging if they all exit without a match
+    drop(sender);
+
+    let expected = prefix.expected_attempts();
+    eprintln!(
+        "Searching for an address starting with \"{}\" on {} threads, expecting about {:.0} attempts",
+        prefix.prefix,
+        workers.len(),
+        expected
+    );
+    let start = Instant::now();
+    let keypair = loop {
+        match receiver.recv_timeout(Duration::from_secs(1)) {
+            Ok(keypair) => break keypair,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                let attempts = attempts.load(Ordering::Relaxed);
+                let rate = attempts as f64 / start.elapsed().as_secs_f64();
+                eprint!(
+                    "\r{} attempts, {:.0} keys/s, {:.0}% of expected attempts",
+                    attempts,
+                    rate,
+                    attempts as f64 / expected * 100.0
+                );
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => bail!("Vanity search stopped"),
+        }
+    };
+    for worker in workers {
+        let _ = worker.join();
+    }
+    eprintln!(
+        "\rFound {} after {} attempts in {:.1}s",
+        keypair.public_key(),
+        attempts.load(Ordering::Relaxed),
+        start.elapsed().as_secs_f64()
+    );
+    Ok(keypair)
+}
+
+/// Returns the seed words and seed type to generate the wallet keys from, if
+/// any. The seed words are either entered by the user or, when a new seed is
+/// requested, generated and displayed for backup. HD wallets need a BIP39
+/// seed phrase.
+fn get_seed(
+    seed: &Option<SeedType>,
+    new_seed: bool,
+    word_count: usize,
+    language: Option<Language>,
+    hd: bool,
+) -> Result<(Option<Vec<String>>, Option<&SeedType>)> {
+    match seed {
+        Some(SeedType::Bip39) => (),
+        _ if new_seed || !hd => (),
+        _ => bail!(
+            "--derivation-path, --account and --passphrase require --seed bip39 or --new-seed"
+        ),
+    }
+    if new_seed {
+        return Ok((
+            Some(new_seed_words(
+                word_count,
+                language.unwrap_or(Language::English),
+            )?),
+            Some(&SeedType::Bip39),
+        ));
+    }
+    match seed {
+        Some(seed_type) => Ok((Some(get_seed_words(seed_type, language)?), Some(seed_type))),
+        None => Ok((None, None)),
+    }
+}
+
+/// Generates a new BIP39 seed phrase and displays it once. The user then has
+/// to enter the phrase again to make sure it was written down correctly.
+fn new_seed_words(word_count: usize, language: Language) -> Result<Vec<String>> {
+    use dialoguer::Input;
+    // Every 3 words encode 32 bits of entropy
+    let mut entropy = vec![0u8; word_count / 3 * 4];
+    randombytes::randombytes_into(&mut entropy);
+    let words = entropy_to_mnemonic(&entropy, language)?;
+
+    // Seed words go to stderr to keep them out of any redirected output
+    eprintln!("Write down the following seed words and store them safely.");
+    eprintln!("They are the only way to recover the wallet if the wallet file is lost.\n");
+    for (index, word) in words.iter().enumerate() {
+        eprintln!("{:>2}. {}", index + 1, word);
+    }
+    eprintln!();
+
+    Input::<String>::new()
+        .with_prompt("Enter the seed words again to confirm")
+        .validate_with(|v: &String| {
+            // Compare NFKD normalized words so differently composed accents
+            // still match
+            let normalize = |w: &str| w.to_lowercase().nfkd().collect::<String>();
+            let entered = v.split_whitespace().map(normalize);
+            if entered.eq(words.iter().map(|w| normalize(w))) {
+                Ok(())
+            } else {
+                Err("Seed words do not match")
+            }
+        })
+        .interact()?;
+    Ok(words)
+}
+
 fn gen_keypair(
     tag: KeyTag,
     seed_words: Option<Vec<String>>,
     seed_type: Option<&SeedType>,
+    language: Option<Language>,
+    derivation_path: Option<DerivationPath>,
+    passphrase: &str,
 ) -> Result<Keypair> {
-    // Callers of this function should either have Some of both or None of both.
+    // Callers of this function should either have Some of both seed words and
+    // seed type or None of both, and a derivation path only with a BIP39 seed.
     // Anything else is an error.
-    match (seed_words, seed_type) {
-        (Some(words), Some(seed_type)) => {
-            let entropy = mnemonic_to_entropy(words, seed_type)?;
+    match (seed_words, seed_type, derivation_path) {
+        (Some(words), Some(SeedType::Bip39), Some(path)) => {
+            let seed = mnemonic_to_seed(&words, passphrase);
+            derive_keypair(tag, &seed[..], &path)
+        }
+        (Some(words), Some(seed_type), None) => {
+            let entropy = mnemonic_to_entropy(words, seed_type, language)?;
             Keypair::generate_from_entropy(tag, &entropy)
         }
-        (None, None) => Ok(Keypair::generate(tag)),
+        (None, None, None) => Ok(Keypair::generate(tag)),
         _ => bail!("Invalid parameters in gen_keypair(). Report this to the development team."),
     }
 }
@@ -163,3 +678,66 @@ fn open_output_file(filename: &Path, create: bool) -> io::Result<fs::File> {
         .create_new(create)
         .open(filename)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ED25519: KeyTag = KeyTag {
+        network: Network::MainNet,
+        key_type: KeyType::Ed25519,
+    };
+    const ECC_COMPACT: KeyTag = KeyTag {
+        network: Network::MainNet,
+        key_type: KeyType::EccCompact,
+    };
+
+    #[test]
+    fn vanity_non_base58() {
+        for c in &["0", "O", "I", "l"] {
+            let err = VanityPrefix::new(&format!("13{}", c), false, ED25519)
+                .err()
+                .expect("non base58 prefix");
+            assert!(err.to_string().contains("base58"));
+        }
+        // Only "0" has no base58 character in either case
+        assert!(VanityPrefix::new("130", true, ED25519).is_err());
+        assert!(VanityPrefix::new("13O", true, ED25519).is_ok());
+    }
+
+    #[test]
+    fn vanity_ignore_case() {
+        let prefix = VanityPrefix::new("13ab", true, ED25519).expect("prefix");
+        assert!(prefix.matches("13AB"));
+        assert!(prefix.matches("13aBcd"));
+        assert!(!prefix.matches("13ac"));
+        assert!(!prefix.matches("13a"));
+
+        let prefix = VanityPrefix::new("13ab", false, ED25519).expect("prefix");
+        assert!(prefix.matches("13abcd"));
+        assert!(!prefix.matches("13AB"));
+    }
+
+    #[test]
+    fn vanity_unreachable() {
+        // Mainnet ed25519 addresses range from "12wk..." to "14tV..."
+        assert!(VanityPrefix::new("13", false, ED25519).is_ok());
+        assert!(VanityPrefix::new("12w", false, ED25519).is_ok());
+        assert!(VanityPrefix::new("12v", false, ED25519).is_err());
+        assert!(VanityPrefix::new("15", false, ED25519).is_err());
+        assert!(VanityPrefix::new("11", false, ED25519).is_err());
+        // and mainnet ecc_compact addresses all start with "11"
+        assert!(VanityPrefix::new("112", false, ECC_COMPACT).is_ok());
+        assert!(VanityPrefix::new("13", false, ECC_COMPACT).is_err());
+    }
+
+    #[test]
+    fn vanity_expected_attempts() {
+        // The second character of an ed25519 address is one of "2", "3" or
+        // "4", any base58 character can follow it
+        let prefix = VanityPrefix::new("13a", false, ED25519).expect("prefix");
+        assert_eq!(174.0, prefix.expected_attempts());
+        let prefix = VanityPrefix::new("13a", true, ED25519).expect("prefix");
+        assert_eq!(87.0, prefix.expected_attempts());
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{
+    cmd::*,
+    keypair::Keypair,
+    result::{bail, Result},
+};
+use prettytable::Table;
+use serde_json::json;
+use std::{
+    fs,
+    io::{self, Write},
+};
+use zeroize::Zeroizing;
+
+arg_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum KeyFormat {
+        Raw,
+        Hex,
+        B58,
+        Pkcs8,
+        Jwk,
+    }
+}
+
+/// Export the decrypted keypair of a wallet.
+///
+/// The exported key is NOT encrypted. Anyone with access to it controls the
+/// wallet's funds. The `raw` format writes the key tag, private key and
+/// public key bytes, which is the same layout as a validator `swarm_key`.
+/// The `hex` and `b58` formats encode those same bytes. The `pkcs8` format
+/// writes a PEM encoded PKCS#8 private key and `jwk` a JSON Web Key.
+#[derive(Debug, StructOpt)]
+pub struct Cmd {
+    /// Format to export the keypair in
+    #[structopt(long,
+                possible_values = &KeyFormat::variants(),
+                case_insensitive = true)]
+    key_format: KeyFormat,
+
+    /// File to write the exported key to. The key is written to standard
+    /// output if not given.
+    #[structopt(short, long)]
+    output: Option<PathBuf>,
+
+    /// Overwrite an existing output file
+    #[structopt(long)]
+    force: bool,
+
+    /// Confirm that the unencrypted private key should be exported
+    #[structopt(long)]
+    confirm: bool,
+
+    /// Allow writing the exported key to standard output when it is a
+    /// terminal
+    #[structopt(long)]
+    allow_tty: bool,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        if !self.confirm {
+            bail!("Exporting writes the unencrypted private key. Use --confirm to export it");
+        }
+        if self.output.is_none() && atty::is(atty::Stream::Stdout) && !self.allow_tty {
+            bail!("Refusing to write the private key to a terminal. Use --output or --allow-tty");
+        }
+        let password = get_password(&opts.password, false)?;
+        let keyfile = opts.keyfile()?;
+        let wallet = load_wallet(opts.files)?;
+        let keypair = wallet.decrypt(password.as_bytes(), keyfile.as_ref())?;
+        let data = encode_keypair(&keypair, self.key_format)?;
+        match &self.output {
+            Some(output) => {
+                let mut writer = open_key_file(output, !self.force)?;
+                writer.write_all(&data)?;
+                print_export(&keypair, self.key_format, output, opts.format)
+            }
+            None => {
+                io::stdout().write_all(&data)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+fn encode_keypair(keypair: &Keypair, format: KeyFormat) -> Result<Zeroizing<Vec<u8>>> {
+    let text = Zeroizing::new(match format {
+        KeyFormat::Raw => return Ok(keypair.to_bytes()),
+        KeyFormat::Hex => hex::encode(&*keypair.to_bytes()),
+        KeyFormat::B58 => bs58::encode(&*keypair.to_bytes()).into_string(),
+        KeyFormat::Pkcs8 => pem_encode("PRIVATE KEY", &keypair.to_pkcs8_der()?),
+        KeyFormat::Jwk => serde_json::to_string_pretty(&keypair.to_jwk()?)?,
+    });
+    Ok(Zeroizing::new(
+        format!("{}\n", text.trim_end()).into_bytes(),
+    ))
+}
+
+fn pem_encode(label: &str, der: &[u8]) -> String {
+    let encoded = base64::encode(der);
+    let lines: Vec<&str> = encoded
+        .as_bytes()
+        .chunks(64)
+        .map(|chunk| std::str::from_utf8(chunk).expect("base64 is ascii"))
+        .collect();
+    format!(
+        "-----BEGIN {label}-----\n{}\n-----END {label}-----",
+        lines.join("\n"),
+        label = label
+    )
+}
+
+/// Opens the file to write an exported key to. On unix systems the file is
+/// only readable and writable by its owner.
+fn open_key_file(filename: &Path, create: bool) -> io::Result<fs::File> {
+    let mut options = fs::OpenOptions::new();
+    options
+        .write(true)
+        .create(true)
+        .create_new(create)
+        .truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(filename)
+}
+
+fn print_export(
+    keypair: &Keypair,
+    key_format: KeyFormat,
+    output: &Path,
+    format: OutputFormat,
+) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.add_row(row!["Key", "Value"]);
+            table.add_row(row!["Address", keypair.public_key()]);
+            table.add_row(row!["Type", keypair.key_type()]);
+            table.add_row(row!["Format", key_format]);
+            table.add_row(row!["File", output.display()]);
+            print_table(&table)
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "address": keypair.public_key().to_string(),
+                "type": keypair.key_type().to_string(),
+                "format": key_format.to_string().to_lowercase(),
+                "file": output.display().to_string(),
+            });
+            print_json(&table)
+        }
+    }
+}
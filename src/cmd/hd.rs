@@ -0,0 +1,150 @@
+use crate::{
+    cmd::*,
+    hd::{DerivationPath, ExtendedKey},
+    keypair::{KeyTag, KeyType, Keypair, Network, NETTYPE_MAIN_STR},
+    mnemonic::{mnemonic_to_seed, Language, SeedType},
+    result::Result,
+};
+use prettytable::Table;
+use zeroize::Zeroizing;
+
+#[derive(Debug, StructOpt)]
+/// Commands for hierarchical deterministic (HD) wallets derived from a BIP39
+/// seed phrase
+pub enum Cmd {
+    List(List),
+}
+
+/// Options to select which key to derive from a BIP39 seed phrase
+#[derive(Debug, StructOpt)]
+pub struct HdOpts {
+    /// Derive the wallet key from the seed phrase using the given SLIP-0010
+    /// derivation path, for example "m/44'/904'/0'/0'/0'"
+    #[structopt(long, conflicts_with = "account")]
+    derivation_path: Option<DerivationPath>,
+
+    /// Derive the wallet key from the seed phrase for the given account
+    /// number, using the derivation path "m/44'/904'/<account>'/0'/0'"
+    #[structopt(long)]
+    account: Option<u32>,
+
+    /// Prompt for a BIP39 passphrase to use with the seed phrase. Without a
+    /// derivation path or account the key for account 0 is derived.
+    #[structopt(long)]
+    passphrase: bool,
+}
+
+impl HdOpts {
+    /// The derivation path selected by these options, if any
+    pub fn derivation_path(&self) -> Result<Option<DerivationPath>> {
+        match (&self.derivation_path, self.account) {
+            (Some(path), _) => Ok(Some(path.clone())),
+            (None, Some(account)) => Ok(Some(DerivationPath::account(account)?)),
+            (None, None) if self.passphrase => Ok(Some(DerivationPath::account(0)?)),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// The BIP39 passphrase to use, prompting for it if requested. The
+    /// passphrase is empty if not requested.
+    pub fn passphrase(&self) -> Result<Zeroizing<String>> {
+        if self.passphrase {
+            Ok(get_passphrase()?)
+        } else {
+            Ok(Zeroizing::new(String::new()))
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+/// List the addresses of the first accounts derived from a BIP39 seed
+/// phrase. This helps find which account numbers hold funds.
+pub struct List {
+    /// Number of accounts to list
+    #[structopt(long, default_value = "10")]
+    count: u32,
+
+    /// First account number to list
+    #[structopt(long, default_value = "0")]
+    start: u32,
+
+    #[structopt(long, default_value = NETTYPE_MAIN_STR)]
+    /// The network of the listed addresses (testnet/mainnet)
+    network: Network,
+
+    /// Prompt for a BIP39 passphrase to use with the seed phrase
+    #[structopt(long)]
+    passphrase: bool,
+
+    #[structopt(long, possible_values = &Language::variants(), case_insensitive = true)]
+    /// The language of the seed phrase. Detected from the words if not given
+    seed_language: Option<Language>,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::List(cmd) => cmd.run(opts).await,
+        }
+    }
+}
+
+impl List {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let seed_words = get_seed_words(&SeedType::Bip39, self.seed_language)?;
+        let passphrase = if self.passphrase {
+            get_passphrase()?
+        } else {
+            Zeroizing::new(String::new())
+        };
+        let seed = mnemonic_to_seed(&seed_words, &passphrase);
+        let tag = KeyTag {
+            network: self.network,
+            key_type: KeyType::Ed25519,
+        };
+        let mut accounts = vec![];
+        for account in self.start..self.start.saturating_add(self.count) {
+            let path = DerivationPath::account(account)?;
+            let keypair = derive_keypair(tag, &seed[..], &path)?;
+            accounts.push((account, path, keypair.public_key().to_string()));
+        }
+        print_accounts(&accounts, opts.format)
+    }
+}
+
+/// Derives the ed25519 keypair at the given derivation path from a BIP39
+/// seed
+pub fn derive_keypair(tag: KeyTag, seed: &[u8], path: &DerivationPath) -> Result<Keypair> {
+    match tag.key_type {
+        KeyType::Ed25519 => (),
+        _ => bail!("HD wallets are only supported for ed25519 keys"),
+    }
+    let key = ExtendedKey::derive(seed, path)?;
+    Keypair::generate_from_entropy(tag, &key.key)
+}
+
+fn print_accounts(accounts: &[(u32, DerivationPath, String)], format: OutputFormat) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.add_row(row!["Account", "Derivation Path", "Address"]);
+            for (account, path, address) in accounts {
+                table.add_row(row![account, path, address]);
+            }
+            print_table(&table)
+        }
+        OutputFormat::Json => {
+            let table: Vec<serde_json::Value> = accounts
+                .iter()
+                .map(|(account, path, address)| {
+                    json!({
+                        "account": account,
+                        "derivation_path": path.to_string(),
+                        "address": address,
+                    })
+                })
+                .collect();
+            print_json(&table)
+        }
+    }
+}
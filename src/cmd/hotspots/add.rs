@@ -11,9 +11,8 @@ use crate::{
 /// get the transaction signed by the DeWi staking server.
 pub struct Cmd {
     /// Base64 encoded transaction to sign. If no transaction is given stdin is
-    /// read for the transaction. Note that the stdin feature only works if the
-    /// wallet password is set in the HELIUM_WALLET_PASSWORD environment
-    /// variable
+    /// read for the transaction. The wallet password can then be given with
+    /// any password source other than file descriptor 0
     #[structopt(name = "TRANSACTION")]
     txn: Option<Transaction>,
 
@@ -28,18 +27,19 @@ pub struct Cmd {
 
 impl Cmd {
     pub async fn run(self, opts: Opts) -> Result {
-        let mut txn = BlockchainTxnAddGatewayV1::from_envelope(&read_txn(&self.txn)?)?;
+        let mut txn =
+            BlockchainTxnAddGatewayV1::from_envelope(&read_txn(&self.txn, &opts.password)?)?;
 
-        let password = get_password(false)?;
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let signer = wallet_signer(&wallet, &opts.password, keyfile.as_ref())?;
 
-        let staking_client = staking::Client::default();
-        let client = helium_api::Client::new_with_base_url(api_url(wallet.public_key.network));
+        let staking_client = staking_client(&opts.config);
+        let client = api_client(&opts.config, wallet.public_key.network);
 
-        let wallet_key = keypair.public_key();
+        let wallet_key = signer.public_key();
 
-        txn.owner_signature = txn.sign(&keypair)?;
+        txn.owner_signature = txn.sign(&signer)?;
         let envelope = match PublicKey::from_bytes(&txn.payer)? {
             key if &key == wallet_key => {
                 txn.payer_signature = txn.owner_signature.clone();
@@ -14,7 +14,7 @@ use helium_api::{
 /// onboarding key to get the transaction signed by the DeWi staking server.
 pub struct Cmd {
     /// Address of hotspot to assert
-    #[structopt(long)]
+    #[structopt(long, parse(try_from_str = parse_address))]
     gateway: PublicKey,
 
     /// Lattitude of hotspot location to assert.
@@ -55,12 +55,12 @@ pub struct Cmd {
 
 impl Cmd {
     pub async fn run(self, opts: Opts) -> Result {
-        let password = get_password(false)?;
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let signer = wallet_signer(&wallet, &opts.password, keyfile.as_ref())?;
 
-        let staking_client = staking::Client::default();
-        let client = helium_api::Client::new_with_base_url(api_url(wallet.public_key.network));
+        let staking_client = staking_client(&opts.config);
+        let client = api_client(&opts.config, wallet.public_key.network);
         let hotspot = hotspots::get(&client, &self.gateway.to_string()).await?;
         let gain: i32 = if let Some(gain) = self.gain.or(hotspot.gain) {
             gain.into()
@@ -73,7 +73,7 @@ impl Cmd {
             bail!("no elevation specified or found on chain")
         };
 
-        let wallet_key = keypair.public_key();
+        let wallet_key = signer.public_key();
         let hotspot = helium_api::hotspots::get(&client, &self.gateway.to_string()).await?;
         // Get the next likely gateway nonce for the new transaction
         let nonce = hotspot.speculative_nonce + 1;
@@ -102,7 +102,7 @@ impl Cmd {
         txn.fee = txn.txn_fee(fees)?;
         txn.staking_fee = txn.txn_mode_staking_fee(&mode, fees)?;
 
-        txn.owner_signature = txn.sign(&keypair)?;
+        txn.owner_signature = txn.sign(&signer)?;
 
         let envelope = if self.onboarding {
             if self.commit {
@@ -10,20 +10,20 @@ use prettytable::{format, Table};
 /// Get the list of hotspots for one or more wallet addresses
 pub struct Cmd {
     /// Addresses to get hotspots for
-    #[structopt(short = "a", long = "address")]
+    #[structopt(short = "a", long = "address", parse(try_from_str = parse_address))]
     addresses: Vec<PublicKey>,
 }
 
 impl Cmd {
     pub async fn run(&self, opts: Opts) -> Result {
         let addresses = collect_addresses(opts.files, self.addresses.clone())?;
-        let api_url = api_url(
+        let client = api_client(
+            &opts.config,
             addresses
                 .first()
                 .map(|key| key.network)
                 .ok_or_else(|| anyhow!("at least one address expected"))?,
         );
-        let client = Client::new_with_base_url(api_url);
         let mut results: Vec<(PublicKey, Result<Vec<Hotspot>>)> =
             Vec::with_capacity(self.addresses.len());
         for address in addresses {
@@ -19,8 +19,10 @@ pub enum Cmd {
 #[derive(Debug, StructOpt)]
 pub struct Sell {
     /// Public address of gateway to be transferred
+    #[structopt(parse(try_from_str = parse_address))]
     gateway: PublicKey,
     /// The recipient of the gateway transfer
+    #[structopt(parse(try_from_str = parse_address))]
     buyer: PublicKey,
     /// Price in HNT to be paid by recipient of transfer
     price: Option<Hnt>,
@@ -28,10 +30,9 @@ pub struct Sell {
 
 #[derive(Debug, StructOpt)]
 pub struct Buy {
-    /// Base64 encoded transaction to sign. If no transaction if given
-    /// stdin is read for the transaction. Note that the stdin feature
-    /// only works if the wallet password is set in the
-    /// HELIUM_WALLET_PASSWORD environment variable
+    /// Base64 encoded transaction to sign. If no transaction is given stdin is
+    /// read for the transaction. The wallet password can then be given with
+    /// any password source other than file descriptor 0
     #[structopt(name = "TRANSACTION")]
     txn: Option<Transaction>,
     #[structopt(long)]
@@ -41,8 +42,9 @@ pub struct Buy {
 
 impl Cmd {
     pub async fn run(self, opts: Opts) -> Result {
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
-        let client = Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = api_client(&opts.config, wallet.public_key.network);
 
         match self {
             Self::Sell(sell) => {
@@ -59,15 +61,14 @@ impl Cmd {
                     buyer_nonce: buyer_account.speculative_nonce + 1,
                 };
                 txn.fee = txn.txn_fee(&get_txn_fees(&client).await?)?;
-                let password = get_password(false)?;
-                let keypair = wallet.decrypt(password.as_bytes())?;
-                txn.seller_signature = txn.sign(&keypair)?;
+                let signer = wallet_signer(&wallet, &opts.password, keyfile.as_ref())?;
+                txn.seller_signature = txn.sign(&signer)?;
                 println!("{}", txn.in_envelope().to_b64()?);
                 Ok(())
             }
 
             Self::Buy(buy) => {
-                let mut envelope = read_txn(&buy.txn)?;
+                let mut envelope = read_txn(&buy.txn, &opts.password)?;
 
                 match &mut envelope.txn {
                     Some(Txn::TransferHotspot(t)) => {
@@ -86,9 +87,8 @@ impl Cmd {
                             bail!("Hotspot transfer nonce no longer valid");
                         }
 
-                        let password = get_password(false)?;
-                        let keypair = wallet.decrypt(password.as_bytes())?;
-                        t.buyer_signature = t.sign(&keypair)?;
+                        let signer = wallet_signer(&wallet, &opts.password, keyfile.as_ref())?;
+                        t.buyer_signature = t.sign(&signer)?;
 
                         let status = maybe_submit_txn(buy.commit, &client, &envelope).await?;
                         print_txn(&envelope, &status, opts.format)
@@ -19,6 +19,7 @@ pub enum Cmd {
 /// The transaction is not submitted to the system unless the '--commit' option is given.
 pub struct Create {
     /// The address of the intended payee for this HTLC
+    #[structopt(parse(try_from_str = parse_address))]
     payee: PublicKey,
 
     /// Number of hnt to send
@@ -42,6 +43,7 @@ pub struct Create {
 /// Redeem the balance from an HTLC address with the specified preimage for the hashlock
 pub struct Redeem {
     /// Address of the HTLC contract to redeem from
+    #[structopt(parse(try_from_str = parse_address))]
     address: PublicKey,
 
     /// The preimage used to create the hashlock for this contract address
@@ -68,12 +70,12 @@ impl Cmd {
 
 impl Create {
     pub async fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
-        let client = Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = api_client(&opts.config, wallet.public_key.network);
 
-        let keypair = wallet.decrypt(password.as_bytes())?;
-        let wallet_address = keypair.public_key();
+        let signer = wallet_signer(&wallet, &opts.password, keyfile.as_ref())?;
+        let wallet_address = signer.public_key();
         let account = accounts::get(&client, &wallet_address.to_string()).await?;
         let address = Keypair::generate(wallet_address.key_tag());
 
@@ -89,7 +91,7 @@ impl Create {
             signature: Vec::new(),
         };
         txn.fee = txn.txn_fee(&get_txn_fees(&client).await?)?;
-        txn.signature = txn.sign(&keypair)?;
+        txn.signature = txn.sign(&signer)?;
         let envelope = txn.in_envelope();
 
         let status = maybe_submit_txn(self.commit, &client, &envelope).await?;
@@ -105,10 +107,12 @@ fn print_create_txn(
 ) -> Result {
     match format {
         OutputFormat::Table => {
+            let contacts = address_book();
+            let payee = PublicKey::from_bytes(&txn.payee)?;
             ptable!(
                 ["Key", "Value"],
                 ["Address", PublicKey::from_bytes(&txn.address)?.to_string()],
-                ["Payee", PublicKey::from_bytes(&txn.payee)?.to_string()],
+                ["Payee", contacts.display(&payee)],
                 ["Amount (HNT)", Hnt::from(txn.amount)],
                 ["Fee (DC)", txn.fee],
                 ["Hashlock", hex::encode(&txn.hashlock)],
@@ -137,20 +141,20 @@ fn print_create_txn(
 
 impl Redeem {
     pub async fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
-        let client = Client::new_with_base_url(api_url(wallet.public_key.network));
+        let signer = wallet_signer(&wallet, &opts.password, keyfile.as_ref())?;
+        let client = api_client(&opts.config, wallet.public_key.network);
 
         let mut txn = BlockchainTxnRedeemHtlcV1 {
             fee: 0,
-            payee: keypair.public_key().to_vec(),
+            payee: signer.public_key().to_vec(),
             address: self.address.to_vec(),
             preimage: self.preimage.clone().into_bytes(),
             signature: Vec::new(),
         };
         txn.fee = txn.txn_fee(&get_txn_fees(&client).await?)?;
-        txn.signature = txn.sign(&keypair)?;
+        txn.signature = txn.sign(&signer)?;
 
         let envelope = txn.in_envelope();
         let status = maybe_submit_txn(self.commit, &client, &envelope).await?;
@@ -166,9 +170,10 @@ fn print_redeem_txn(
 ) -> Result {
     match format {
         OutputFormat::Table => {
+            let payee = PublicKey::from_bytes(&txn.payee)?;
             ptable!(
                 ["Key", "Value"],
-                ["Payee", PublicKey::from_bytes(&txn.payee)?.to_string()],
+                ["Payee", address_book().display(&payee)],
                 ["Address", PublicKey::from_bytes(&txn.address)?.to_string()],
                 ["Preimage", std::str::from_utf8(&txn.preimage)?],
                 ["Hash", status_str(status)]
@@ -0,0 +1,254 @@
+use crate::{
+    cmd::{export::KeyFormat, pwhash::PwHashOpts, *},
+    format::{self, Format},
+    keypair::{Keypair, Network, PublicKey, NETTYPE_MAIN_STR},
+    result::{anyhow, bail, Result},
+    wallet::Wallet,
+};
+use std::{
+    fs,
+    io::{self, Read},
+};
+use zeroize::Zeroizing;
+
+#[derive(Debug, StructOpt)]
+/// Import an existing unencrypted key into a new encrypted wallet
+pub enum Cmd {
+    Basic(Basic),
+    Sharded(Sharded),
+}
+
+/// Options describing the key to import
+#[derive(Debug, StructOpt)]
+pub struct KeyInput {
+    /// File to read the key from. The key is read from standard input if not
+    /// given.
+    #[structopt(short, long)]
+    input: Option<PathBuf>,
+
+    /// Format of the key to import. Detected from the key data if not given.
+    /// The `raw`, `hex` and `b58` formats hold the key tag, private key and
+    /// public key bytes as written by `validators generate`, `export` or a
+    /// `swarm_key` file.
+    #[structopt(long,
+                possible_values = &KeyFormat::variants(),
+                case_insensitive = true)]
+    key_format: Option<KeyFormat>,
+
+    /// The network of the wallet (testnet/mainnet). Only used for the pkcs8
+    /// and jwk formats since the other formats include the network.
+    #[structopt(long, default_value = NETTYPE_MAIN_STR)]
+    network: Network,
+
+    /// The expected address of the imported key. The import fails if the
+    /// key does not match it.
+    #[structopt(long, parse(try_from_str = parse_address))]
+    address: Option<PublicKey>,
+}
+
+#[derive(Debug, StructOpt)]
+/// Import a key into a new basic wallet
+pub struct Basic {
+    #[structopt(flatten)]
+    key: KeyInput,
+
+    #[structopt(short, long, default_value = "wallet.key")]
+    /// Output file to store the key in
+    output: PathBuf,
+
+    #[structopt(long)]
+    /// Overwrite an existing file
+    force: bool,
+
+    #[structopt(flatten)]
+    pwhash: PwHashOpts,
+
+    #[structopt(flatten)]
+    metadata: MetadataOpts,
+}
+
+#[derive(Debug, StructOpt)]
+/// Import a key into a new sharded wallet
+pub struct Sharded {
+    #[structopt(flatten)]
+    key: KeyInput,
+
+    #[structopt(short, long, default_value = "wallet.key")]
+    /// Output file to store the key in
+    output: PathBuf,
+
+    #[structopt(long)]
+    /// Overwrite an existing file
+    force: bool,
+
+    #[structopt(flatten)]
+    pwhash: PwHashOpts,
+
+    #[structopt(flatten)]
+    metadata: MetadataOpts,
+
+    #[structopt(short = "n", long = "shards", default_value = "5")]
+    /// Number of shards to break the key into
+    key_share_count: u8,
+
+    #[structopt(short = "k", long = "required-shards", default_value = "3")]
+    /// Number of shards required to recover the key
+    recovery_threshold: u8,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::Basic(cmd) => cmd.run(opts).await,
+            Cmd::Sharded(cmd) => cmd.run(opts).await,
+        }
+    }
+}
+
+impl Basic {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let keypair = self.key.read_keypair()?;
+        let password = get_password(&opts.password, true)?;
+        let keyfile = opts.keyfile()?;
+        let format = format::Basic {
+            pwhash: self.pwhash.pwhash()?,
+        };
+        let wallet = Wallet::encrypt(
+            &keypair,
+            password.as_bytes(),
+            keyfile.as_ref(),
+            Format::Basic(format),
+            self.metadata.new_metadata(None),
+        )?;
+        let mut writer = open_output_file(&self.output, !self.force)?;
+        wallet.write(&mut writer)?;
+        verify::print_result(&wallet, true, opts.format)
+    }
+}
+
+impl Sharded {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let keypair = self.key.read_keypair()?;
+        let password = get_password(&opts.password, true)?;
+        let keyfile = opts.keyfile()?;
+        let format = format::Sharded {
+            key_share_count: self.key_share_count,
+            recovery_threshold: self.recovery_threshold,
+            pwhash: self.pwhash.pwhash()?,
+            key_shares: vec![],
+        };
+        let wallet = Wallet::encrypt(
+            &keypair,
+            password.as_bytes(),
+            keyfile.as_ref(),
+            Format::Sharded(format),
+            self.metadata.new_metadata(None),
+        )?;
+
+        let filenames = shard_filenames(&self.output, self.key_share_count);
+        for (filename, shard) in filenames.iter().zip(wallet.shards()?) {
+            let mut writer = open_output_file(filename, !self.force)?;
+            shard.write(&mut writer)?;
+        }
+        verify::print_result(&wallet, true, opts.format)
+    }
+}
+
+impl KeyInput {
+    /// Reads and decodes the key to import and checks it against the
+    /// expected address, if given
+    fn read_keypair(&self) -> Result<Keypair> {
+        let data = match &self.input {
+            Some(input) => read_key_data(fs::File::open(input)?)?,
+            None => read_key_data(io::stdin())?,
+        };
+        let key_format = match self.key_format {
+            Some(key_format) => key_format,
+            None => detect_key_format(&data),
+        };
+        let keypair = decode_keypair(&data, key_format, self.network)?;
+        match &self.address {
+            Some(address) if address != keypair.public_key() => bail!(
+                "Imported key has address {} but {} was expected",
+                keypair.public_key(),
+                address
+            ),
+            _ => Ok(keypair),
+        }
+    }
+}
+
+/// The largest key input accepted, far more than any supported key format
+/// needs
+const MAX_KEY_DATA_LENGTH: usize = 16 * 1024;
+
+/// Reads all key data from the given reader into a buffer that is allocated
+/// once, so growing it does not leave copies of the key behind
+fn read_key_data(mut reader: impl Read) -> Result<Zeroizing<Vec<u8>>> {
+    let mut data = Zeroizing::new(vec![0u8; MAX_KEY_DATA_LENGTH]);
+    let mut len = 0;
+    loop {
+        if len == data.len() {
+            bail!("Key data is larger than {} bytes", MAX_KEY_DATA_LENGTH);
+        }
+        match reader.read(&mut data[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        }
+    }
+    data.truncate(len);
+    Ok(data)
+}
+
+/// Guesses the format of the given key data. Binary data is assumed to be
+/// in the raw format.
+fn detect_key_format(data: &[u8]) -> KeyFormat {
+    let text = match std::str::from_utf8(data) {
+        Ok(text) => text.trim(),
+        Err(_) => return KeyFormat::Raw,
+    };
+    if text.starts_with("-----BEGIN") {
+        KeyFormat::Pkcs8
+    } else if text.starts_with('{') {
+        KeyFormat::Jwk
+    } else if hex::decode(text).is_ok() {
+        KeyFormat::Hex
+    } else if bs58::decode(text).into_vec().is_ok() {
+        KeyFormat::B58
+    } else {
+        KeyFormat::Raw
+    }
+}
+
+fn decode_keypair(data: &[u8], format: KeyFormat, network: Network) -> Result<Keypair> {
+    match format {
+        KeyFormat::Raw => Keypair::from_tagged_bytes(data),
+        KeyFormat::Hex => {
+            Keypair::from_tagged_bytes(&Zeroizing::new(hex::decode(key_text(data)?)?))
+        }
+        KeyFormat::B58 => {
+            Keypair::from_tagged_bytes(&Zeroizing::new(bs58::decode(key_text(data)?).into_vec()?))
+        }
+        KeyFormat::Pkcs8 => {
+            Keypair::from_pkcs8_der(&pem_decode("PRIVATE KEY", key_text(data)?)?, network)
+        }
+        KeyFormat::Jwk => Keypair::from_jwk(&serde_json::from_str(key_text(data)?)?, network),
+    }
+}
+
+fn key_text(data: &[u8]) -> Result<&str> {
+    Ok(std::str::from_utf8(data)?.trim())
+}
+
+fn pem_decode(label: &str, text: &str) -> Result<Vec<u8>> {
+    let begin = format!("-----BEGIN {}-----", label);
+    let end = format!("-----END {}-----", label);
+    let body = text
+        .strip_prefix(&begin)
+        .and_then(|rest| rest.trim_end().strip_suffix(&end))
+        .ok_or_else(|| anyhow!("Expected a PEM encoded unencrypted {}", label))?;
+    let body: String = body.split_whitespace().collect();
+    Ok(base64::decode(body)?)
+}
@@ -12,7 +12,7 @@ pub struct Cmd {
     qr_code: bool,
 
     /// Display basic information on a given public key
-    #[structopt(long)]
+    #[structopt(long, parse(try_from_str = parse_address))]
     address: Option<PublicKey>,
 }
 
@@ -26,7 +26,7 @@ impl Cmd {
                     print_qr(&wallet.public_key.to_string())?;
                     Ok(())
                 } else {
-                    let client = Client::new_with_base_url(api_url(wallet.public_key.network));
+                    let client = api_client(&opts.config, wallet.public_key.network);
                     let account = accounts::get(&client, &wallet.address()?).await?;
                     print_wallet(&wallet, &account, opts.format)
                 }
@@ -65,6 +65,9 @@ fn print_wallet(wallet: &Wallet, account: &Account, format: OutputFormat) -> Res
             table.add_row(row!["Type", wallet.public_key.key_tag().key_type]);
             table.add_row(row!["Sharded", wallet.is_sharded()]);
             table.add_row(row!["PwHash", wallet.pwhash()]);
+            table.add_row(row!["Key Slots", key_slot_count(wallet)]);
+            table.add_row(row!["Keyfile", wallet.keyfile]);
+            add_metadata_rows(&mut table, &wallet.metadata);
             table.add_row(row!["Balance", account.balance]);
             table.add_row(row!["DC Balance", account.dc_balance]);
             table.add_row(row!["Securities Balance", account.sec_balance]);
@@ -76,9 +79,20 @@ fn print_wallet(wallet: &Wallet, account: &Account, format: OutputFormat) -> Res
                 "network": wallet.public_key.key_tag().network.to_string(),
                 "type": wallet.public_key.key_tag().key_type.to_string(),
                 "pwhash": wallet.pwhash().to_string(),
+                "keyfile": wallet.keyfile,
+                "key_slots": key_slot_count(wallet),
+                "metadata": metadata_json(&wallet.metadata),
                 "account": account,
             });
             print_json(&table)
         }
     }
 }
+
+/// The number of key slots of the wallet, zero if it does not use key slots
+fn key_slot_count(wallet: &Wallet) -> usize {
+    wallet
+        .slots_format()
+        .map(|format| format.key_slots.len())
+        .unwrap_or(0)
+}
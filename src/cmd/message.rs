@@ -0,0 +1,226 @@
+use crate::{
+    cmd::*,
+    keypair::PublicKey,
+    message::{self, Encoding, SignedMessage},
+    result::{bail, Result},
+};
+use prettytable::Table;
+use serde_json::json;
+use std::io::{Read, Write};
+
+#[derive(Debug, StructOpt)]
+/// Sign, verify and encrypt messages.
+///
+/// Messages are signed with a "Helium Signed Message" prefix, so a message
+/// signature can not be used as a transaction signature. Messages can be
+/// encrypted to any ed25519 address and decrypted by its wallet.
+pub enum Cmd {
+    Sign(Sign),
+    Verify(Verify),
+    Encrypt(Encrypt),
+    Decrypt(Decrypt),
+}
+
+#[derive(Debug, StructOpt)]
+/// Sign a message with the wallet key. The json output is an envelope with
+/// the address, message and signature that "message verify" accepts.
+pub struct Sign {
+    /// The message to sign, for example a challenge from a partner
+    message: String,
+
+    /// Encoding of the message (utf8, hex, base64)
+    #[structopt(long, default_value = "utf8")]
+    encoding: Encoding,
+}
+
+#[derive(Debug, StructOpt)]
+/// Verify a signed message given either as a json envelope or as address,
+/// signature and message
+pub struct Verify {
+    /// Json envelope with the address, message and signature, as printed
+    /// by "message sign". Use "-" to read the envelope from stdin
+    #[structopt(long, conflicts_with_all = &["address", "signature", "message"])]
+    envelope: Option<PathBuf>,
+
+    /// Address that signed the message
+    #[structopt(long, parse(try_from_str = parse_address), required_unless = "envelope")]
+    address: Option<PublicKey>,
+
+    /// Hex or base64 encoded signature
+    #[structopt(long, required_unless = "envelope")]
+    signature: Option<String>,
+
+    /// The signed message
+    #[structopt(required_unless = "envelope")]
+    message: Option<String>,
+
+    /// Encoding of the message (utf8, hex, base64)
+    #[structopt(long, default_value = "utf8")]
+    encoding: Encoding,
+}
+
+#[derive(Debug, StructOpt)]
+/// Encrypt a message to an address. The armored output can be pasted into
+/// chat or email and only the wallet of the address can decrypt it.
+pub struct Encrypt {
+    /// Address to encrypt the message to
+    #[structopt(long, parse(try_from_str = parse_address))]
+    to: PublicKey,
+
+    /// The message to encrypt. Read from stdin if not given
+    message: Option<String>,
+}
+
+#[derive(Debug, StructOpt)]
+/// Decrypt an armored message encrypted to the wallet address and write it
+/// to stdout
+pub struct Decrypt {
+    /// File with the armored message. Read from stdin if not given
+    input: Option<PathBuf>,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::Sign(cmd) => cmd.run(opts).await,
+            Cmd::Verify(cmd) => cmd.run(opts).await,
+            Cmd::Encrypt(cmd) => cmd.run(opts).await,
+            Cmd::Decrypt(cmd) => cmd.run(opts).await,
+        }
+    }
+}
+
+impl Sign {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let password = get_password(&opts.password, false)?;
+        let keyfile = opts.keyfile()?;
+        let wallet = load_wallet(opts.files)?;
+        let keypair = wallet.decrypt(password.as_bytes(), keyfile.as_ref())?;
+
+        let signed = SignedMessage::sign(&keypair, &self.message, self.encoding)?;
+        let signature = signed.signature()?;
+        match opts.format {
+            OutputFormat::Table => {
+                ptable!(
+                    ["Key", "Value"],
+                    ["Address", signed.address],
+                    ["Message", signed.message],
+                    ["Encoding", signed.encoding],
+                    ["Signature (base64)", signed.signature],
+                    ["Signature (hex)", hex::encode(&signature)]
+                );
+                Ok(())
+            }
+            OutputFormat::Json => {
+                let mut table = serde_json::to_value(&signed)?;
+                table["signature_hex"] = hex::encode(&signature).into();
+                print_json(&table)
+            }
+        }
+    }
+}
+
+impl Verify {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let signed: SignedMessage = match &self.envelope {
+            Some(path) if path.as_os_str() == "-" => serde_json::from_reader(io::stdin())?,
+            Some(path) => serde_json::from_reader(fs::File::open(path)?)?,
+            None => SignedMessage {
+                address: self
+                    .address
+                    .as_ref()
+                    .map(|a| a.to_string())
+                    .unwrap_or_default(),
+                message: self.message.clone().unwrap_or_default(),
+                encoding: self.encoding,
+                signature: self.signature.clone().unwrap_or_default(),
+            },
+        };
+        signed.verify()?;
+        match opts.format {
+            OutputFormat::Table => {
+                let mut table = Table::new();
+                table.add_row(row!["Key", "Value"]);
+                table.add_row(row!["Address", signed.address]);
+                table.add_row(row!["Message", signed.message]);
+                table.add_row(row!["Valid", true]);
+                print_table(&table)
+            }
+            OutputFormat::Json => {
+                let table = json!({
+                    "address": signed.address,
+                    "message": signed.message,
+                    "valid": true,
+                });
+                print_json(&table)
+            }
+        }
+    }
+}
+
+impl Encrypt {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let plaintext = match &self.message {
+            Some(message) => message.as_bytes().to_vec(),
+            None => {
+                let mut plaintext = Vec::new();
+                io::stdin().read_to_end(&mut plaintext)?;
+                plaintext
+            }
+        };
+        let armored = message::encrypt(&self.to, &plaintext)?;
+        match opts.format {
+            OutputFormat::Table => {
+                print!("{}", armored);
+                Ok(())
+            }
+            OutputFormat::Json => {
+                let table = json!({
+                    "to": self.to.to_string(),
+                    "message": armored,
+                });
+                print_json(&table)
+            }
+        }
+    }
+}
+
+impl Decrypt {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let armored = match &self.input {
+            Some(path) => fs::read_to_string(path)?,
+            None => {
+                if opts.password.reads_stdin() {
+                    bail!("The message can not be read from stdin when the password is");
+                }
+                let mut armored = String::new();
+                io::stdin().read_to_string(&mut armored)?;
+                armored
+            }
+        };
+        let to = message::encrypted_to(&armored)?;
+        let keyfile = opts.keyfile()?;
+        let wallet = load_wallet(opts.files)?;
+        if to != wallet.public_key {
+            bail!("Message is encrypted to {}, not this wallet", to);
+        }
+        let password = get_password(&opts.password, false)?;
+        let keypair = wallet.decrypt(password.as_bytes(), keyfile.as_ref())?;
+
+        let plaintext = message::decrypt(&keypair, &armored)?;
+        match opts.format {
+            OutputFormat::Table => {
+                let mut stdout = io::stdout();
+                stdout.write_all(&plaintext)?;
+                Ok(stdout.flush()?)
+            }
+            OutputFormat::Json => {
+                let table = json!({
+                    "to": to.to_string(),
+                    "message": String::from_utf8_lossy(&plaintext),
+                });
+                print_json(&table)
+            }
+        }
+    }
+}
@@ -1,8 +1,19 @@
+#[cfg(unix)]
+use crate::agent::AgentClient;
+pub use crate::contacts::parse_address;
 use crate::{
-    keypair::{Network, PublicKey},
+    config::Config,
+    contacts::AddressBook,
+    format::Keyfile,
+    hd::DerivationPath,
+    keypair::{Keypair, Network, PublicKey},
+    keyring::Keyring,
+    metadata::Metadata,
     mnemonic,
-    result::{bail, Error, Result},
-    traits::{TxnFeeConfig, B64},
+    password_source::{PasswordSource, PASSWORD_ENV},
+    result::{anyhow, bail, Error, Result},
+    staking,
+    traits::{TxnFeeConfig, TxnSigner, B64},
     wallet::Wallet,
 };
 pub use helium_api::{
@@ -17,24 +28,39 @@ use std::{
     path::{Path, PathBuf},
 };
 pub use structopt::{clap::arg_enum, StructOpt};
+use zeroize::Zeroizing;
 
+#[cfg(unix)]
+pub mod agent;
 pub mod balance;
 pub mod burn;
 pub mod commit;
+pub mod config;
+pub mod contacts;
 pub mod create;
+pub mod export;
+pub mod hd;
 pub mod hotspots;
 pub mod htlc;
+pub mod import;
 pub mod info;
+pub mod message;
 pub mod multisig;
 pub mod oracle;
 pub mod oui;
+pub mod password;
 pub mod pay;
+pub mod pwhash;
 pub mod request;
+pub mod reshard;
 pub mod securities;
+pub mod slots;
+pub mod txn;
 pub mod upgrade;
 pub mod validators;
 pub mod vars;
 pub mod verify;
+pub mod wallets;
 
 arg_enum! {
     #[derive(Debug)]
@@ -47,21 +73,168 @@ arg_enum! {
 /// Common options for most wallet commands
 #[derive(Debug, StructOpt)]
 pub struct Opts {
-    /// File(s) to use
-    #[structopt(
-        short = "f",
-        long = "file",
-        number_of_values(1),
-        default_value = "wallet.key"
-    )]
+    /// File(s) to use. Defaults to the default wallet of the keyring, or
+    /// wallet.key if no default wallet is set
+    #[structopt(short = "f", long = "file", number_of_values(1))]
     files: Vec<PathBuf>,
 
-    /// Output format to use
+    /// Name of a wallet in the keyring to use instead of wallet file(s)
+    #[structopt(long, conflicts_with = "files")]
+    wallet: Option<String>,
+
+    /// Output format to use. Defaults to the configured format, or table
     #[structopt(long = "format",
                 possible_values = &["table", "json"],
-                case_insensitive = true,
-                default_value = "table")]
+                case_insensitive = true)]
+    output_format: Option<OutputFormat>,
+
+    /// The effective output format
+    #[structopt(skip = OutputFormat::Table)]
     format: OutputFormat,
+
+    /// Keyfile needed in addition to the password to decrypt the wallet, or
+    /// to protect a newly created wallet with
+    #[structopt(long)]
+    keyfile: Option<PathBuf>,
+
+    /// Read the wallet password from the first line of the given file
+    #[structopt(long, conflicts_with_all = &["password-fd", "password-command"])]
+    password_file: Option<PathBuf>,
+
+    /// Read the wallet password from the first line read from the given
+    /// open file descriptor
+    #[structopt(long, conflicts_with = "password-command")]
+    password_fd: Option<i32>,
+
+    /// Run the given shell command and use the first line of its output as
+    /// the wallet password
+    #[structopt(long)]
+    password_command: Option<String>,
+
+    /// Settings from the config files
+    #[structopt(skip)]
+    config: Config,
+
+    /// The effective password source
+    #[structopt(skip)]
+    password: PasswordSource,
+}
+
+impl Opts {
+    /// Loads the config files and applies the configured output format and
+    /// password source unless they were given on the command line
+    pub fn load_config(mut self) -> Result<Self> {
+        self.config = Config::load()?;
+        self.password = if let Some(file) = self.password_file.take() {
+            PasswordSource::File(file)
+        } else if let Some(fd) = self.password_fd {
+            PasswordSource::Fd(fd)
+        } else if let Some(command) = self.password_command.take() {
+            PasswordSource::Command(command)
+        } else if env::var_os(PASSWORD_ENV).is_some() {
+            PasswordSource::Env
+        } else {
+            self.config
+                .password
+                .source()
+                .unwrap_or(PasswordSource::Prompt)
+        };
+        self.format = match (self.output_format.take(), &self.config.format) {
+            (Some(format), _) => format,
+            (None, Some(format)) => format
+                .parse()
+                .map_err(|_| anyhow!("Invalid output format \"{}\" in config", format))?,
+            (None, None) => OutputFormat::Table,
+        };
+        Ok(self)
+    }
+
+    /// Resolves the wallet file(s) to use. A wallet named with --wallet is
+    /// looked up in the keyring. Without --wallet or --file the configured
+    /// wallet or the default wallet of the keyring is used, falling back to
+    /// wallet.key. A configured or default wallet that can not be found
+    /// only gives a warning, since not every command reads the wallet.
+    pub fn resolve_wallet(mut self) -> Result<Self> {
+        if let Some(name) = &self.wallet {
+            self.files = Keyring::open()?.files(name)?;
+        } else if self.files.is_empty() {
+            let name = match &self.config.wallet {
+                Some(name) => Ok(Some(name.clone())),
+                None => match Keyring::open() {
+                    Ok(keyring) => keyring.default_name(),
+                    Err(_) => Ok(None),
+                },
+            };
+            let files = name.and_then(|name| match name {
+                Some(name) => Keyring::open()?.files(&name).map(Some),
+                None => Ok(None),
+            });
+            self.files = match files {
+                Ok(Some(files)) => files,
+                Ok(None) => vec![PathBuf::from("wallet.key")],
+                Err(err) => {
+                    eprintln!("Warning: {}, using wallet.key", err);
+                    vec![PathBuf::from("wallet.key")]
+                }
+            };
+        }
+        Ok(self)
+    }
+
+    /// Reads the keyfile given with --keyfile, if any
+    fn keyfile(&self) -> Result<Option<Keyfile>> {
+        self.keyfile
+            .as_ref()
+            .map(|path| Keyfile::read(path))
+            .transpose()
+    }
+}
+
+/// Options for the metadata stored with a wallet
+#[derive(Debug, StructOpt)]
+pub struct MetadataOpts {
+    /// A label to store with the wallet
+    #[structopt(long)]
+    label: Option<String>,
+
+    /// Notes to store with the wallet
+    #[structopt(long)]
+    notes: Option<String>,
+}
+
+impl MetadataOpts {
+    /// Metadata for a new wallet, if there is any to store. The creation
+    /// time is recorded along with a label, notes or the derivation path of
+    /// keys derived from a seed phrase. Without any of those there is no
+    /// metadata, so the wallet stays readable by releases without metadata
+    /// support.
+    pub fn new_metadata(&self, derivation_path: Option<DerivationPath>) -> Option<Metadata> {
+        if self.label.is_none() && self.notes.is_none() && derivation_path.is_none() {
+            return None;
+        }
+        Some(Metadata {
+            label: self.label.clone(),
+            notes: self.notes.clone(),
+            derivation_path,
+            ..Metadata::now()
+        })
+    }
+
+    /// Updates the metadata of an existing wallet with the given label and
+    /// notes, if any
+    pub fn update_metadata(&self, metadata: Option<Metadata>) -> Option<Metadata> {
+        if self.label.is_none() && self.notes.is_none() {
+            return metadata;
+        }
+        let mut metadata = metadata.unwrap_or_default();
+        if self.label.is_some() {
+            metadata.label = self.label.clone();
+        }
+        if self.notes.is_some() {
+            metadata.notes = self.notes.clone();
+        }
+        Some(metadata)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -75,56 +248,301 @@ impl std::str::FromStr for Transaction {
     }
 }
 
-fn load_wallet(files: Vec<PathBuf>) -> Result<Wallet> {
-    let mut files_iter = files.iter();
-    let mut first_wallet = match files_iter.next() {
-        Some(path) => {
-            let mut reader = fs::File::open(path)?;
-            Wallet::read(&mut reader)?
+/// A wallet read from one or more wallet files. The names of the files are
+/// kept so that shard files can be named when decrypting.
+pub struct LoadedWallet {
+    wallet: Wallet,
+    files: Vec<PathBuf>,
+}
+
+impl std::ops::Deref for LoadedWallet {
+    type Target = Wallet;
+
+    fn deref(&self) -> &Wallet {
+        &self.wallet
+    }
+}
+
+impl std::ops::DerefMut for LoadedWallet {
+    fn deref_mut(&mut self) -> &mut Wallet {
+        &mut self.wallet
+    }
+}
+
+impl LoadedWallet {
+    /// Decrypts the wallet. When a set of shards other than the first K
+    /// given decrypts a sharded wallet, at least one of the given shards is
+    /// bad, and the shard files that were not used are named in a warning.
+    pub fn decrypt(&self, password: &[u8], keyfile: Option<&Keyfile>) -> Result<Keypair> {
+        if !self.wallet.is_sharded() {
+            return self.wallet.decrypt(password, keyfile);
+        }
+        let (keypair, subset) = match self.wallet.decrypt_key_shares(password, keyfile)? {
+            Some(found) => found,
+            None => bail!(
+                "Failed to decrypt wallet: wrong password or corrupt/foreign shard. \
+                 Use \"verify\" to check the shard files"
+            ),
+        };
+        if subset.iter().enumerate().any(|(i, index)| i != *index) {
+            let unused: Vec<String> = self
+                .files
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !subset.contains(index))
+                .map(|(_, file)| file.display().to_string())
+                .collect();
+            eprintln!(
+                "Warning: shard file(s) not used to decrypt the wallet: {}. \
+                 Use \"verify\" to check them",
+                unused.join(", ")
+            );
         }
+        Ok(keypair)
+    }
+
+    pub fn into_wallet(self) -> Wallet {
+        self.wallet
+    }
+}
+
+fn load_wallet(files: Vec<PathBuf>) -> Result<LoadedWallet> {
+    let wallets = read_wallet_files(&files)?;
+    let problems = shard_file_problems(&wallets);
+    let bad_files: Vec<String> = wallets
+        .iter()
+        .zip(problems)
+        .filter_map(|((path, _), problem)| {
+            problem.map(|problem| format!("{}: {}", path.display(), problem))
+        })
+        .collect();
+    if !bad_files.is_empty() {
+        bail!("Bad shard file(s):\n{}", bad_files.join("\n"));
+    }
+
+    let mut wallets_iter = wallets.into_iter();
+    let mut first_wallet = match wallets_iter.next() {
+        Some((_, wallet)) => wallet,
         None => bail!("At least one wallet file expected"),
     };
+    for (_, wallet) in wallets_iter {
+        first_wallet.absorb_shard(&wallet)?;
+    }
+
+    Ok(LoadedWallet {
+        wallet: first_wallet,
+        files,
+    })
+}
 
-    for path in files_iter {
+fn read_wallet_files(files: &[PathBuf]) -> Result<Vec<(PathBuf, Wallet)>> {
+    if files.is_empty() {
+        bail!("At least one wallet file expected");
+    }
+    let mut wallets = Vec::with_capacity(files.len());
+    for path in files {
         let mut reader = fs::File::open(path)?;
-        let w = Wallet::read(&mut reader)?;
-        first_wallet.absorb_shard(&w)?;
+        let wallet =
+            Wallet::read(&mut reader).map_err(|err| anyhow!("{}: {}", path.display(), err))?;
+        wallets.push((path.clone(), wallet));
+    }
+    Ok(wallets)
+}
+
+/// Checks that the given wallet files are shards of the same wallet and
+/// returns, for each file, a description of what is wrong with it. The
+/// public key shared by most of the files is taken to be the wallet's public
+/// key. A single wallet file never has problems.
+fn shard_file_problems(wallets: &[(PathBuf, Wallet)]) -> Vec<Option<String>> {
+    if wallets.len() < 2 {
+        return vec![None; wallets.len()];
+    }
+    let key_count = |public_key: &PublicKey| {
+        wallets
+            .iter()
+            .filter(|(_, wallet)| &wallet.public_key == public_key)
+            .count()
+    };
+    // max_by_key picks the last maximum, so search in reverse to prefer the
+    // first file on a tie
+    let public_key = &wallets
+        .iter()
+        .rev()
+        .max_by_key(|(_, wallet)| key_count(&wallet.public_key))
+        .expect("wallets")
+        .1
+        .public_key;
+    let reference = wallets
+        .iter()
+        .find_map(|(_, wallet)| match wallet.sharded_format() {
+            Ok(format) if &wallet.public_key == public_key => Some(format),
+            _ => None,
+        });
+
+    let mut seen: Vec<(u8, &Path)> = vec![];
+    wallets
+        .iter()
+        .map(|(path, wallet)| {
+            let format = match wallet.sharded_format() {
+                Ok(format) => format,
+                Err(_) => return Some("not a shard of a sharded wallet".to_string()),
+            };
+            if &wallet.public_key != public_key {
+                return Some(format!(
+                    "belongs to a different wallet ({})",
+                    wallet.public_key
+                ));
+            }
+            if let Some(reference) = reference {
+                if format.key_share_count != reference.key_share_count
+                    || format.recovery_threshold != reference.recovery_threshold
+                {
+                    return Some(format!(
+                        "expected a {} of {} shard, found {} of {}",
+                        reference.recovery_threshold,
+                        reference.key_share_count,
+                        format.recovery_threshold,
+                        format.key_share_count
+                    ));
+                }
+            }
+            for key_share in &format.key_shares {
+                let index = key_share.index();
+                if let Some((_, other)) = seen.iter().find(|(seen_index, _)| *seen_index == index) {
+                    return Some(format!(
+                        "duplicate of shard {} in {}",
+                        index,
+                        other.display()
+                    ));
+                }
+                seen.push((index, path));
+            }
+            None
+        })
+        .collect()
+}
+
+/// Reads the wallet password from the given source
+fn get_password(source: &PasswordSource, confirm: bool) -> Result<Zeroizing<String>> {
+    source.read(confirm)
+}
+
+/// Signs transactions for a wallet, either with its decrypted keypair or
+/// through a wallet agent that holds the unlocked wallet
+enum WalletSigner {
+    Keypair(Keypair),
+    #[cfg(unix)]
+    Agent(AgentClient, PublicKey),
+}
+
+impl WalletSigner {
+    fn public_key(&self) -> &PublicKey {
+        match self {
+            Self::Keypair(keypair) => keypair.public_key(),
+            #[cfg(unix)]
+            Self::Agent(_, public_key) => public_key,
+        }
     }
+}
 
-    Ok(first_wallet)
+impl TxnSigner for WalletSigner {
+    fn sign_txn(&self, envelope: &BlockchainTxn, msg: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::Keypair(keypair) => keypair.sign_txn(envelope, msg),
+            #[cfg(unix)]
+            Self::Agent(agent, _) => agent.sign_txn(envelope, msg),
+        }
+    }
 }
 
-fn get_password(confirm: bool) -> std::io::Result<String> {
-    match env::var("HELIUM_WALLET_PASSWORD") {
-        Ok(str) => Ok(str),
+/// Gets a signer for the wallet. A running agent that holds the unlocked
+/// wallet is used if there is one, otherwise the wallet is decrypted with
+/// the password from the given source.
+fn wallet_signer(
+    wallet: &LoadedWallet,
+    password: &PasswordSource,
+    keyfile: Option<&Keyfile>,
+) -> Result<WalletSigner> {
+    #[cfg(unix)]
+    if let Some(agent) = AgentClient::for_wallet(&wallet.public_key)? {
+        return Ok(WalletSigner::Agent(agent, wallet.public_key.clone()));
+    }
+    let password = get_password(password, false)?;
+    let keypair = wallet.decrypt(password.as_bytes(), keyfile)?;
+    Ok(WalletSigner::Keypair(keypair))
+}
+
+fn get_new_password() -> std::io::Result<Zeroizing<String>> {
+    match env::var("HELIUM_WALLET_NEW_PASSWORD") {
+        Ok(str) => Ok(Zeroizing::new(str)),
         _ => {
             use dialoguer::Password;
-            let mut builder = Password::new();
-            builder.with_prompt("Password");
-            if confirm {
-                builder.with_confirmation("Confirm password", "Passwords do not match");
-            };
-            builder.interact()
+            Password::new()
+                .with_prompt("New password")
+                .with_confirmation("Confirm new password", "Passwords do not match")
+                .interact()
+                .map(Zeroizing::new)
+        }
+    }
+}
+
+fn get_passphrase() -> std::io::Result<Zeroizing<String>> {
+    match env::var("HELIUM_WALLET_PASSPHRASE") {
+        Ok(str) => Ok(Zeroizing::new(str)),
+        _ => {
+            use dialoguer::Password;
+            Password::new()
+                .with_prompt("Seed passphrase")
+                .with_confirmation("Confirm seed passphrase", "Passphrases do not match")
+                .interact()
+                .map(Zeroizing::new)
         }
     }
 }
 
 const DEFAULT_TESTNET_BASE_URL: &str = "https://testnet-api.helium.wtf/v1";
 
-fn api_url(network: Network) -> String {
+/// The API URL for the given network. The `HELIUM_API_URL` and
+/// `HELIUM_TESTNET_API_URL` environment variables take precedence over the
+/// configured URLs.
+fn api_url(config: &Config, network: Network) -> String {
     match network {
-        Network::MainNet => {
-            env::var("HELIUM_API_URL").unwrap_or_else(|_| helium_api::DEFAULT_BASE_URL.to_string())
-        }
+        Network::MainNet => env::var("HELIUM_API_URL")
+            .ok()
+            .or_else(|| config.api.mainnet.clone())
+            .unwrap_or_else(|| helium_api::DEFAULT_BASE_URL.to_string()),
         Network::TestNet => env::var("HELIUM_TESTNET_API_URL")
-            .unwrap_or_else(|_| DEFAULT_TESTNET_BASE_URL.to_string()),
+            .ok()
+            .or_else(|| config.api.testnet.clone())
+            .unwrap_or_else(|| DEFAULT_TESTNET_BASE_URL.to_string()),
     }
 }
 
-fn read_txn(txn: &Option<Transaction>) -> Result<BlockchainTxn> {
+fn api_client(config: &Config, network: Network) -> Client {
+    Client::new_with_timeout(api_url(config, network), config.timeout())
+}
+
+fn staking_url(config: &Config) -> String {
+    config
+        .api
+        .staking
+        .clone()
+        .unwrap_or_else(|| staking::DEFAULT_BASE_URL.to_string())
+}
+
+fn staking_client(config: &Config) -> staking::Client {
+    staking::Client::new_with_timeout(staking_url(config), config.timeout())
+}
+
+/// Returns the given transaction or reads one from stdin. Reading from
+/// stdin fails if the password is read from stdin as well.
+fn read_txn(txn: &Option<Transaction>, password: &PasswordSource) -> Result<BlockchainTxn> {
     match txn {
         Some(txn) => Ok(txn.0.clone()),
         None => {
+            if password.reads_stdin() {
+                bail!("Can not read both the transaction and the password from stdin");
+            }
             let mut buffer = String::new();
             io::stdin().read_line(&mut buffer)?;
             Ok(buffer.trim().parse::<Transaction>()?.0)
@@ -144,36 +562,112 @@ fn collect_addresses(files: Vec<PathBuf>, mut addresses: Vec<PublicKey>) -> Resu
     Ok(addresses)
 }
 
-fn get_seed_words(seed_type: &mnemonic::SeedType) -> Result<Vec<String>> {
-    use dialoguer::Input;
-    let split_str = |s: &String| s.split_whitespace().map(|w| w.to_string()).collect();
-    let word_string = Input::<String>::new()
-        .with_prompt("Space separated seed words")
-        .validate_with(|v: &String| {
-            let word_list = split_str(v);
-            match mnemonic::mnemonic_to_entropy(word_list, seed_type) {
-                Ok(_) => Ok(()),
-                Err(err) => Err(err),
+/// Prompts for seed words. Unique four letter prefixes of words are
+/// accepted. Unknown words and words that fail the checksum can be corrected
+/// one at a time without entering all the words again. The full seed words
+/// are returned.
+fn get_seed_words(
+    seed_type: &mnemonic::SeedType,
+    language: Option<mnemonic::Language>,
+) -> Result<Vec<String>> {
+    use dialoguer::{Input, Select};
+    let read_words = || -> Result<Vec<String>> {
+        let word_string = Input::<String>::new()
+            .with_prompt("Space separated seed words")
+            .interact()?;
+        Ok(word_string
+            .split_whitespace()
+            .map(|w| w.to_string())
+            .collect())
+    };
+    let mut words = read_words()?;
+    loop {
+        let language = match language {
+            Some(language) => language,
+            None => mnemonic::detect_language(&words, seed_type)?,
+        };
+        if let Some(&position) = mnemonic::unknown_words(&words, language).first() {
+            eprintln!(
+                "Seed word {} ({}) not found in wordlist",
+                position + 1,
+                words[position]
+            );
+            let suggestions = mnemonic::suggest_words(&words[position], language);
+            if !suggestions.is_empty() {
+                eprintln!("Did you mean {}?", suggestions.join(", "));
             }
-        })
-        .interact()?;
-    Ok(word_string
-        .split_whitespace()
-        .map(|w| w.to_string())
-        .collect())
+            words[position] = Input::<String>::new()
+                .with_prompt(format!("Seed word {}", position + 1))
+                .interact()?
+                .trim()
+                .to_string();
+            continue;
+        }
+        let err = match mnemonic::mnemonic_to_entropy(words.clone(), seed_type, Some(language)) {
+            Ok(_) => return mnemonic::expand_words(&words, language),
+            Err(err) => err,
+        };
+        eprintln!("{}", err);
+        let corrections = mnemonic::checksum_corrections(&words, seed_type, language);
+        if corrections.is_empty() {
+            words = read_words()?;
+            continue;
+        }
+        const MAX_CORRECTIONS: usize = 10;
+        let mut items: Vec<String> = corrections
+            .iter()
+            .take(MAX_CORRECTIONS)
+            .map(|(position, word)| {
+                format!(
+                    "Replace word {} ({}) with {}",
+                    position + 1,
+                    words[*position],
+                    word
+                )
+            })
+            .collect();
+        items.push("Enter all seed words again".to_string());
+        let selection = Select::new()
+            .with_prompt("Single word corrections with a valid checksum")
+            .items(&items)
+            .default(0)
+            .interact()?;
+        match corrections.get(selection) {
+            Some((position, word)) if selection < MAX_CORRECTIONS => {
+                words[*position] = word.to_string()
+            }
+            _ => words = read_words()?,
+        }
+    }
 }
 
 pub fn get_payer(staking_address: PublicKey, payer: &Option<String>) -> Result<Option<PublicKey>> {
     match payer {
         Some(s) if s == "staking" => Ok(Some(staking_address)),
         Some(s) => {
-            let address = s.parse()?;
+            let address = parse_address(s)?;
             Ok(Some(address))
         }
         None => Ok(None),
     }
 }
 
+/// Deserializes an address that may be given as an `@alias` from the
+/// address book
+pub fn deserialize_address<'de, D>(deserializer: D) -> std::result::Result<PublicKey, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+    parse_address(&s).map_err(serde::de::Error::custom)
+}
+
+/// Opens the address book to show aliases with addresses. An address book
+/// that can not be read shows no aliases.
+pub fn address_book() -> AddressBook {
+    AddressBook::open().unwrap_or_default()
+}
+
 pub async fn get_txn_fees(client: &Client) -> Result<TxnFeeConfig> {
     let vars = helium_api::vars::get(client).await?;
     if vars.contains_key("txn_fees") {
@@ -198,6 +692,74 @@ pub fn open_output_file(filename: &Path, create: bool) -> io::Result<fs::File> {
         .open(filename)
 }
 
+/// Returns the file names for the N shards of a sharded wallet with the given
+/// base file name, i.e. `wallet.key.1` through `wallet.key.N`.
+pub fn shard_filenames(filename: &Path, count: u8) -> Vec<PathBuf> {
+    let extension = get_file_extension(filename);
+    (1..=count)
+        .map(|i| {
+            let mut shard_filename = filename.to_path_buf();
+            shard_filename.set_extension(format!("{}.{}", extension, i));
+            shard_filename
+        })
+        .collect()
+}
+
+/// Returns the base file name for a given shard file name by stripping a
+/// numeric shard extension, i.e. `wallet.key.3` becomes `wallet.key`.
+pub fn shard_base_filename(filename: &Path) -> PathBuf {
+    match filename.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if !ext.is_empty() && ext.chars().all(|c| c.is_ascii_digit()) => {
+            filename.with_extension("")
+        }
+        _ => filename.to_path_buf(),
+    }
+}
+
+/// Writes the given wallets to the given files, replacing each file
+/// atomically. All wallets are first written to temporary files next to
+/// their destination, and only once all of them have been written are they
+/// renamed into place one by one. If any write fails the temporary files
+/// are removed and the existing files are left untouched. If a rename
+/// fails the files renamed before it have already been replaced; the
+/// remaining temporary files are removed.
+pub fn write_wallets_atomic(filenames: &[PathBuf], wallets: &[Wallet]) -> Result {
+    if filenames.len() != wallets.len() {
+        bail!("Expected one file name per wallet");
+    }
+    let tmp_filenames: Vec<PathBuf> = filenames
+        .iter()
+        .map(|filename| {
+            let mut tmp_filename = filename.as_os_str().to_owned();
+            tmp_filename.push(".tmp");
+            PathBuf::from(tmp_filename)
+        })
+        .collect();
+    let write_tmp = |tmp_filename: &Path, wallet: &Wallet| -> Result {
+        let mut writer = open_output_file(tmp_filename, true)?;
+        wallet.write(&mut writer)?;
+        writer.sync_all()?;
+        Ok(())
+    };
+    for (tmp_filename, wallet) in tmp_filenames.iter().zip(wallets) {
+        if let Err(err) = write_tmp(tmp_filename, wallet) {
+            for tmp_filename in &tmp_filenames {
+                let _ = fs::remove_file(tmp_filename);
+            }
+            return Err(err);
+        }
+    }
+    for (index, (tmp_filename, filename)) in tmp_filenames.iter().zip(filenames).enumerate() {
+        if let Err(err) = fs::rename(tmp_filename, filename) {
+            for tmp_filename in &tmp_filenames[index..] {
+                let _ = fs::remove_file(tmp_filename);
+            }
+            return Err(err.into());
+        }
+    }
+    Ok(())
+}
+
 pub fn get_file_extension(filename: &Path) -> String {
     use std::ffi::OsStr;
     filename
@@ -225,6 +787,39 @@ pub fn print_table(table: &prettytable::Table) -> Result {
     Ok(())
 }
 
+/// Adds a row for each field of the given wallet metadata to a key/value
+/// table
+pub fn add_metadata_rows(table: &mut prettytable::Table, metadata: &Option<Metadata>) {
+    let metadata = match metadata {
+        Some(metadata) => metadata,
+        None => return,
+    };
+    if let Some(label) = &metadata.label {
+        table.add_row(row!["Label", label]);
+    }
+    if let Some(created_at) = metadata.created_at_str() {
+        table.add_row(row!["Created", created_at]);
+    }
+    if let Some(notes) = &metadata.notes {
+        table.add_row(row!["Notes", notes]);
+    }
+    if let Some(derivation_path) = &metadata.derivation_path {
+        table.add_row(row!["Derivation Path", derivation_path]);
+    }
+}
+
+pub fn metadata_json(metadata: &Option<Metadata>) -> serde_json::Value {
+    match metadata {
+        Some(metadata) => json!({
+            "label": metadata.label,
+            "created_at": metadata.created_at_str(),
+            "notes": metadata.notes,
+            "derivation_path": metadata.derivation_path.as_ref().map(|path| path.to_string()),
+        }),
+        None => json!(null),
+    }
+}
+
 pub fn status_str(status: &Option<PendingTxnStatus>) -> &str {
     status.as_ref().map_or("none", |s| &s.hash)
 }
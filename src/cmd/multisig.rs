@@ -91,9 +91,10 @@ impl Inspect {
 
 impl Prove {
     pub async fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
+        let password = get_password(&opts.password, false)?;
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let keypair = wallet.decrypt(password.as_bytes(), keyfile.as_ref())?;
 
         let txn = Artifact::load_txn(&self.artifact)?;
         let mut proofs = Proofs::new();
@@ -108,7 +109,7 @@ impl Prove {
 }
 
 impl Combine {
-    pub async fn run(&self, _opts: Opts) -> Result {
+    pub async fn run(&self, opts: Opts) -> Result {
         let mut envelope = Artifact::load_txn(&self.artifact)?;
         // Load proofs and key_proof maps from txn
         let mut combined_proofs = Proofs::from_txn(&envelope)?;
@@ -118,7 +119,7 @@ impl Combine {
         }
         combined_proofs.apply(&mut envelope)?;
 
-        let client = Client::new_with_base_url(api_url(self.network));
+        let client = api_client(&opts.config, self.network);
         let status = maybe_submit_txn(self.commit, &client, &envelope).await?;
         print_txn(&envelope, &status)
     }
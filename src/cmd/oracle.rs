@@ -44,20 +44,20 @@ impl Cmd {
 
 impl Report {
     pub async fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let signer = wallet_signer(&wallet, &opts.password, keyfile.as_ref())?;
 
-        let client = Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = api_client(&opts.config, wallet.public_key.network);
         let block_height = self.block.to_block(&client).await?;
         let price = u64::from(self.price.to_usd().await?);
         let mut txn = BlockchainTxnPriceOracleV1 {
-            public_key: keypair.public_key().into(),
+            public_key: signer.public_key().into(),
             price,
             block_height,
             signature: Vec::new(),
         };
-        txn.signature = txn.sign(&keypair)?;
+        txn.signature = txn.sign(&signer)?;
 
         let envelope = txn.in_envelope();
         let status = maybe_submit_txn(self.commit, &client, &envelope).await?;
@@ -2,7 +2,7 @@ use crate::{
     cmd::oui::*,
     traits::{TxnEnvelope, TxnFee, TxnSign, TxnStakingFee},
 };
-use helium_api::{ouis, Client};
+use helium_api::ouis;
 use structopt::StructOpt;
 
 /// Allocates an Organizational Unique Identifier (OUI) which
@@ -11,7 +11,7 @@ use structopt::StructOpt;
 #[derive(Debug, StructOpt)]
 pub struct Create {
     /// The address(es) of the router to send packets to
-    #[structopt(long = "address", short = "a", number_of_values(1))]
+    #[structopt(long = "address", short = "a", number_of_values(1), parse(try_from_str = parse_address))]
     addresses: Vec<PublicKey>,
 
     /// Optionally indicate last OUI. Wallet will determine
@@ -35,7 +35,7 @@ pub struct Create {
 
     /// Payer for the transaction (B58 address). If not specified the
     /// wallet is used.
-    #[structopt(long)]
+    #[structopt(long, parse(try_from_str = parse_address))]
     payer: Option<PublicKey>,
 
     /// Commit the transaction to the API. If the staking server is
@@ -48,12 +48,12 @@ pub struct Create {
 
 impl Create {
     pub async fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
-        let wallet_key = keypair.public_key();
+        let signer = wallet_signer(&wallet, &opts.password, keyfile.as_ref())?;
+        let wallet_key = signer.public_key();
 
-        let client = Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = api_client(&opts.config, wallet.public_key.network);
 
         let oui = if let Some(oui) = self.last_oui {
             oui
@@ -63,7 +63,7 @@ impl Create {
 
         let mut txn = BlockchainTxnOuiV1 {
             addresses: map_addresses(self.addresses.clone(), |v| v.to_vec())?,
-            owner: keypair.public_key().into(),
+            owner: signer.public_key().into(),
             payer: self.payer.as_ref().map_or(vec![], |v| v.into()),
             oui,
             fee: 0,
@@ -79,7 +79,7 @@ impl Create {
         txn.fee = txn.txn_fee(fees)?;
         txn.staking_fee = txn.txn_staking_fee(fees)?;
 
-        txn.owner_signature = txn.sign(&keypair)?;
+        txn.owner_signature = txn.sign(&signer)?;
         let envelope = txn.in_envelope();
 
         match self.payer.as_ref() {
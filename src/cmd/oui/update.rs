@@ -2,7 +2,7 @@ use crate::{
     cmd::oui::*,
     traits::{TxnEnvelope, TxnFee, TxnSign, TxnStakingFee, B64},
 };
-use helium_api::{models::PendingTxnStatus, ouis, Client};
+use helium_api::{models::PendingTxnStatus, ouis};
 use serde_json::json;
 use structopt::StructOpt;
 
@@ -27,7 +27,7 @@ pub struct Routers {
     #[structopt(required = true, long)]
     pub oui: u32,
     /// The address(es) of the router to send packets to
-    #[structopt(required = true, long = "address", short = "a", number_of_values(1))]
+    #[structopt(required = true, long = "address", short = "a", number_of_values(1), parse(try_from_str = parse_address))]
     pub addresses: Vec<PublicKey>,
     /// Which OUI nonce this transaction has
     #[structopt(long)]
@@ -101,10 +101,10 @@ pub struct RequestSubnet {
 
 impl Update {
     pub async fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
-        let client = Client::new_with_base_url(api_url(wallet.public_key.network));
+        let signer = wallet_signer(&wallet, &opts.password, keyfile.as_ref())?;
+        let client = api_client(&opts.config, wallet.public_key.network);
 
         let (oui, commit, nonce, update) = match self {
             Update::Routers(routers) => (
@@ -143,7 +143,7 @@ impl Update {
         let mut txn = BlockchainTxnRoutingV1 {
             // the type in the proto diverges from the more common u64
             oui: oui as u32,
-            owner: keypair.public_key().into(),
+            owner: signer.public_key().into(),
             fee: 0,
             signature: vec![],
             staking_fee: 0,
@@ -157,7 +157,7 @@ impl Update {
         let fees = get_txn_fees(&client).await?;
         txn.fee = txn.txn_fee(&fees)?;
         txn.staking_fee = txn.txn_staking_fee(&fees)?;
-        txn.signature = txn.sign(&keypair)?;
+        txn.signature = txn.sign(&signer)?;
         let envelope = txn.in_envelope();
 
         let status = maybe_submit_txn(commit, &client, &envelope).await?;
@@ -0,0 +1,134 @@
+use crate::{cmd::*, format::KeySlot, result::Result, wallet::Wallet};
+
+#[derive(Debug, StructOpt)]
+/// Manage the password of a wallet
+pub enum Cmd {
+    Change(Change),
+}
+
+#[derive(Debug, StructOpt)]
+/// Change the password of a wallet.
+///
+/// The wallet is decrypted with the current password and encrypted again
+/// with the new password. The wallet format, including the password hash
+/// parameters and the number of shards for sharded wallets, is kept. A
+/// sharded wallet needs at least K of its shards and is written out as a
+/// new full set of N shards; shards protected by the old password can not
+/// be combined with the new ones. For a key slot wallet only the slot
+/// unlocked by the current password is changed.
+pub struct Change {
+    #[structopt(short, long)]
+    /// Output file to store the wallet in. Defaults to the (first) given
+    /// wallet file, or its base name for a sharded wallet
+    output: Option<PathBuf>,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::Change(cmd) => cmd.run(opts).await,
+        }
+    }
+}
+
+impl Change {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let output = match &self.output {
+            Some(output) => output.clone(),
+            None => match opts.files.first() {
+                Some(file) => file.clone(),
+                None => bail!("At least one wallet file expected"),
+            },
+        };
+        let password = get_password(&opts.password, false)?;
+        let keyfile = opts.keyfile()?;
+        let mut wallet = load_wallet(opts.files)?;
+        let keypair = wallet.decrypt(password.as_bytes(), keyfile.as_ref())?;
+
+        let new_password = get_new_password()?;
+        if new_password == password {
+            bail!("New password is the same as the current password");
+        }
+        if let Ok(format) = wallet.mut_slots_format() {
+            let (index, data_key) = format.unseal(password.as_bytes(), keyfile.as_ref())?;
+            if let Ok((other, _)) = format.unseal(new_password.as_bytes(), keyfile.as_ref()) {
+                bail!("New password already unlocks slot {}", other);
+            }
+            format.key_slots[index] = KeySlot::seal(
+                &data_key,
+                new_password.as_bytes(),
+                keyfile.as_ref(),
+                format.key_slots[index].pwhash.with_new_salt(),
+            )?;
+            write_wallets_atomic(&[output], std::slice::from_ref(&*wallet))?;
+            return verify::print_result(&wallet, true, opts.format);
+        }
+        let new_wallet = reencrypt(&wallet, &keypair, new_password.as_bytes(), keyfile.as_ref())?;
+
+        if new_wallet.is_sharded() {
+            let shards = new_wallet.shards()?;
+            let filenames = shard_filenames(&shard_base_filename(&output), shards.len() as u8);
+            write_wallets_atomic(&filenames, &shards)?;
+        } else {
+            write_wallets_atomic(&[output], std::slice::from_ref(&new_wallet))?;
+        }
+        verify::print_result(&new_wallet, true, opts.format)
+    }
+}
+
+/// Encrypts the keypair of the given wallet again with a new password. The
+/// wallet format is kept, with a freshly salted password hash and, for a
+/// sharded wallet, a new set of key shares.
+fn reencrypt(
+    wallet: &Wallet,
+    keypair: &Keypair,
+    new_password: &[u8],
+    keyfile: Option<&Keyfile>,
+) -> Result<Wallet> {
+    Wallet::encrypt(
+        keypair,
+        new_password,
+        keyfile,
+        wallet.format.renew(),
+        wallet.metadata.clone(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{format::Format, pwhash::PwHash};
+
+    #[test]
+    fn change_sharded_password() {
+        let keypair = Keypair::default();
+        let format = Format::sharded(5, 3, PwHash::pbkdf2(1000));
+        let wallet =
+            Wallet::encrypt(&keypair, b"old password", None, format, None).expect("wallet");
+        let new_wallet = reencrypt(&wallet, &keypair, b"new password", None).expect("reencrypt");
+
+        assert!(new_wallet.decrypt(b"old password", None).is_err());
+        assert_eq!(
+            keypair,
+            new_wallet.decrypt(b"new password", None).expect("decrypt")
+        );
+        assert_eq!(wallet.public_key, new_wallet.public_key);
+        match &new_wallet.format {
+            Format::Sharded(sharded) => {
+                assert_eq!(5, sharded.key_share_count);
+                assert_eq!(3, sharded.recovery_threshold);
+            }
+            _ => panic!("expected a sharded wallet"),
+        }
+        assert_eq!(5, new_wallet.shards().expect("shards").len());
+
+        assert_eq!(
+            PathBuf::from("wallet.key"),
+            shard_base_filename(Path::new("wallet.key.3"))
+        );
+        assert_eq!(
+            PathBuf::from("wallet.key"),
+            shard_base_filename(Path::new("wallet.key"))
+        );
+    }
+}
@@ -74,21 +74,21 @@ impl Cmd {
     pub async fn run(&self, opts: Opts) -> Result {
         let payments = self.collect_payments()?;
 
-        let password = get_password(false)?;
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
 
-        let client = Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = api_client(&opts.config, wallet.public_key.network);
 
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let signer = wallet_signer(&wallet, &opts.password, keyfile.as_ref())?;
 
         let mut txn = BlockchainTxnPaymentV2 {
             fee: 0,
             payments,
-            payer: keypair.public_key().to_vec(),
+            payer: signer.public_key().to_vec(),
             nonce: if let Some(nonce) = self.nonce() {
                 nonce
             } else {
-                let account = accounts::get(&client, &keypair.public_key().to_string()).await?;
+                let account = accounts::get(&client, &signer.public_key().to_string()).await?;
                 account.speculative_nonce + 1
             },
             signature: Vec::new(),
@@ -99,7 +99,7 @@ impl Cmd {
         } else {
             txn.txn_fee(&get_txn_fees(&client).await?)?
         };
-        txn.signature = txn.sign(&keypair)?;
+        txn.signature = txn.sign(&signer)?;
 
         let envelope = txn.in_envelope();
         let status = maybe_submit_txn(self.commit(), &client, &envelope).await?;
@@ -159,11 +159,12 @@ fn print_txn(
 ) -> Result {
     match format {
         OutputFormat::Table => {
+            let contacts = address_book();
             let mut table = Table::new();
             table.add_row(row!["Payee", "Amount (HNT)", "Memo"]);
             for payment in txn.payments.clone() {
                 table.add_row(row![
-                    PublicKey::from_bytes(payment.payee)?.to_string(),
+                    contacts.display(&PublicKey::from_bytes(payment.payee)?),
                     Hnt::from(payment.amount),
                     Memo::from(payment.memo).to_string(),
                 ]);
@@ -202,7 +203,9 @@ fn print_txn(
 
 #[derive(Debug, Deserialize, StructOpt)]
 pub struct Payee {
-    /// Address to send the tokens to.
+    /// Address to send the tokens to. Contacts can be given as @alias.
+    #[serde(deserialize_with = "deserialize_address")]
+    #[structopt(parse(try_from_str = parse_address))]
     address: PublicKey,
     /// Amount of HNT to send
     amount: Hnt,
@@ -0,0 +1,303 @@
+use crate::{
+    cmd::*,
+    pwhash::{
+        PwHash, ARGON2ID13_MEMLIMIT_MAX, ARGON2ID13_MEMLIMIT_MIN, ARGON2ID13_OPSLIMIT_MAX,
+        ARGON2ID13_OPSLIMIT_MIN,
+    },
+    result::Result,
+};
+use prettytable::Table;
+use serde_json::json;
+use sodiumoxide::crypto::pwhash::argon2id13;
+use std::{
+    convert::TryFrom,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, StructOpt)]
+/// Commands for wallet password hashing
+pub enum Cmd {
+    Benchmark(Benchmark),
+}
+
+arg_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum PwHashKind {
+        Argon2id13,
+        Pbkdf2,
+    }
+}
+
+/// Password hash options for commands that encrypt a wallet
+#[derive(Debug, StructOpt)]
+pub struct PwHashOpts {
+    /// The password hash to use to derive the wallet encryption key
+    #[structopt(long = "pwhash",
+                possible_values = &["argon2id13", "pbkdf2"],
+                case_insensitive = true,
+                default_value = "argon2id13")]
+    kind: PwHashKind,
+
+    /// The Argon2id13 operations limit. Defaults to the libsodium
+    /// "sensitive" limit
+    #[structopt(long)]
+    ops_limit: Option<usize>,
+
+    /// The Argon2id13 memory limit in bytes. Defaults to the libsodium
+    /// "sensitive" limit
+    #[structopt(long)]
+    mem_limit: Option<usize>,
+
+    /// The number of PBKDF2 iterations
+    #[structopt(long)]
+    iterations: Option<u32>,
+}
+
+impl PwHashOpts {
+    /// Construct the password hash described by these options
+    pub fn pwhash(&self) -> Result<PwHash> {
+        match self.kind {
+            PwHashKind::Argon2id13 => {
+                if self.iterations.is_some() {
+                    bail!("--iterations is only supported for pbkdf2");
+                }
+                let ops_limit = self.ops_limit.unwrap_or(argon2id13::OPSLIMIT_SENSITIVE.0);
+                let mem_limit = self.mem_limit.unwrap_or(argon2id13::MEMLIMIT_SENSITIVE.0);
+                validate_argon2id13_limits(ops_limit, mem_limit)?;
+                Ok(PwHash::argon2id13(ops_limit, mem_limit))
+            }
+            PwHashKind::Pbkdf2 => {
+                if self.ops_limit.is_some() || self.mem_limit.is_some() {
+                    bail!("--ops-limit and --mem-limit are only supported for argon2id13");
+                }
+                match self.iterations {
+                    Some(0) => bail!("PBKDF2 iterations must be greater than 0"),
+                    Some(iterations) => Ok(PwHash::pbkdf2(iterations)),
+                    None => Ok(PwHash::pbkdf2_default()),
+                }
+            }
+        }
+    }
+}
+
+fn validate_argon2id13_limits(ops_limit: usize, mem_limit: usize) -> Result {
+    if !(ARGON2ID13_OPSLIMIT_MIN..=ARGON2ID13_OPSLIMIT_MAX).contains(&ops_limit) {
+        bail!(
+            "Argon2id13 ops limit must be between {} and {}",
+            ARGON2ID13_OPSLIMIT_MIN,
+            ARGON2ID13_OPSLIMIT_MAX
+        );
+    }
+    if !(ARGON2ID13_MEMLIMIT_MIN..=ARGON2ID13_MEMLIMIT_MAX).contains(&mem_limit) {
+        bail!(
+            "Argon2id13 mem limit must be between {} and {} bytes",
+            ARGON2ID13_MEMLIMIT_MIN,
+            ARGON2ID13_MEMLIMIT_MAX
+        );
+    }
+    Ok(())
+}
+
+#[derive(Debug, StructOpt)]
+/// Suggest password hash parameters that take about the given target time
+/// to unlock a wallet on this machine.
+///
+/// Note that the suggestion only applies to the machine the benchmark is run
+/// on. Wallets need to be unlocked on the slowest machine they are used on.
+pub struct Benchmark {
+    /// The target unlock time in milliseconds
+    #[structopt(long, default_value = "1000")]
+    target: u64,
+
+    /// The password hash to benchmark
+    #[structopt(long = "pwhash",
+                possible_values = &["argon2id13", "pbkdf2"],
+                case_insensitive = true,
+                default_value = "argon2id13")]
+    kind: PwHashKind,
+
+    /// The Argon2id13 memory limit in bytes to benchmark with. Defaults to
+    /// the libsodium "moderate" limit. The limit is lowered if even a single
+    /// operation exceeds the target time.
+    #[structopt(long)]
+    mem_limit: Option<usize>,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::Benchmark(cmd) => cmd.run(opts).await,
+        }
+    }
+}
+
+impl Benchmark {
+    pub async fn run(&self, opts: Opts) -> Result {
+        if self.target == 0 {
+            bail!("Target time must be greater than 0");
+        }
+        let target = Duration::from_millis(self.target);
+        let pwhash = match self.kind {
+            PwHashKind::Argon2id13 => self.suggest_argon2id13(target)?,
+            PwHashKind::Pbkdf2 => self.suggest_pbkdf2(target)?,
+        };
+        let elapsed = time_pwhash(&pwhash)?;
+        print_suggestion(&pwhash, elapsed, opts.format)
+    }
+
+    fn suggest_argon2id13(&self, target: Duration) -> Result<PwHash> {
+        let mut mem_limit = self.mem_limit.unwrap_or(argon2id13::MEMLIMIT_MODERATE.0);
+        validate_argon2id13_limits(ARGON2ID13_OPSLIMIT_MIN, mem_limit)?;
+        // Hashing time grows roughly linearly with the operations limit, so
+        // time a single operation and scale up. If a single operation is
+        // already too slow halve the memory limit until it fits.
+        loop {
+            let elapsed = time_pwhash(&PwHash::argon2id13(ARGON2ID13_OPSLIMIT_MIN, mem_limit))?;
+            if elapsed <= target || mem_limit / 2 < ARGON2ID13_MEMLIMIT_MIN {
+                let ops_limit = scale(ARGON2ID13_OPSLIMIT_MIN as u64, elapsed, target) as usize;
+                return Ok(PwHash::argon2id13(
+                    ops_limit.max(ARGON2ID13_OPSLIMIT_MIN),
+                    mem_limit,
+                ));
+            }
+            mem_limit /= 2;
+        }
+    }
+
+    fn suggest_pbkdf2(&self, target: Duration) -> Result<PwHash> {
+        const SAMPLE_ITERATIONS: u32 = 10_000;
+        let elapsed = time_pwhash(&PwHash::pbkdf2(SAMPLE_ITERATIONS))?;
+        let iterations = scale(SAMPLE_ITERATIONS as u64, elapsed, target);
+        Ok(PwHash::pbkdf2(
+            u32::try_from(iterations).unwrap_or(u32::MAX).max(1),
+        ))
+    }
+}
+
+/// Scale a given work factor that took `elapsed` time to one that is expected
+/// to take the `target` time.
+fn scale(work: u64, elapsed: Duration, target: Duration) -> u64 {
+    let elapsed = elapsed.as_micros().max(1);
+    (work as u128 * target.as_micros() / elapsed) as u64
+}
+
+fn time_pwhash(pwhash: &PwHash) -> Result<Duration> {
+    let mut key = [0u8; 32];
+    let start = Instant::now();
+    pwhash.pwhash(b"benchmark password", &mut key)?;
+    Ok(start.elapsed())
+}
+
+fn print_suggestion(pwhash: &PwHash, elapsed: Duration, format: OutputFormat) -> Result {
+    let (args, params) = match pwhash {
+        PwHash::Argon2id13(hasher) => (
+            format!(
+                "--pwhash argon2id13 --ops-limit {} --mem-limit {}",
+                hasher.ops_limit(),
+                hasher.mem_limit()
+            ),
+            json!({
+                "ops_limit": hasher.ops_limit(),
+                "mem_limit": hasher.mem_limit(),
+            }),
+        ),
+        PwHash::Pbkdf2(hasher) => (
+            format!("--pwhash pbkdf2 --iterations {}", hasher.iterations()),
+            json!({ "iterations": hasher.iterations() }),
+        ),
+    };
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.add_row(row!["Key", "Value"]);
+            table.add_row(row!["PwHash", pwhash]);
+            match pwhash {
+                PwHash::Argon2id13(hasher) => {
+                    table.add_row(row!["Ops limit", hasher.ops_limit()]);
+                    table.add_row(row!["Mem limit (bytes)", hasher.mem_limit()]);
+                }
+                PwHash::Pbkdf2(hasher) => {
+                    table.add_row(row!["Iterations", hasher.iterations()]);
+                }
+            }
+            table.add_row(row!["Measured (ms)", elapsed.as_millis()]);
+            table.add_row(row!["Options", args]);
+            print_table(&table)
+        }
+        OutputFormat::Json => {
+            let table = json!({
+                "pwhash": pwhash.to_string(),
+                "params": params,
+                "measured_ms": elapsed.as_millis() as u64,
+                "options": args,
+            });
+            print_json(&table)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opts(
+        kind: PwHashKind,
+        ops_limit: Option<usize>,
+        mem_limit: Option<usize>,
+        iterations: Option<u32>,
+    ) -> PwHashOpts {
+        PwHashOpts {
+            kind,
+            ops_limit,
+            mem_limit,
+            iterations,
+        }
+    }
+
+    #[test]
+    fn argon2id13_limits() {
+        let argon2 = |ops_limit, mem_limit| {
+            opts(
+                PwHashKind::Argon2id13,
+                Some(ops_limit),
+                Some(mem_limit),
+                None,
+            )
+            .pwhash()
+        };
+        assert!(argon2(ARGON2ID13_OPSLIMIT_MIN, ARGON2ID13_MEMLIMIT_MIN).is_ok());
+        assert!(argon2(ARGON2ID13_OPSLIMIT_MIN - 1, ARGON2ID13_MEMLIMIT_MIN).is_err());
+        assert!(argon2(ARGON2ID13_OPSLIMIT_MIN, ARGON2ID13_MEMLIMIT_MIN - 1).is_err());
+        assert!(argon2(ARGON2ID13_OPSLIMIT_MAX + 1, ARGON2ID13_MEMLIMIT_MIN).is_err());
+        assert!(argon2(ARGON2ID13_OPSLIMIT_MIN, ARGON2ID13_MEMLIMIT_MAX + 1).is_err());
+        assert!(opts(PwHashKind::Argon2id13, None, None, Some(1000))
+            .pwhash()
+            .is_err());
+    }
+
+    #[test]
+    fn pbkdf2_options() {
+        assert!(opts(PwHashKind::Pbkdf2, None, None, Some(0))
+            .pwhash()
+            .is_err());
+        assert!(opts(PwHashKind::Pbkdf2, None, None, Some(1000))
+            .pwhash()
+            .is_ok());
+        assert!(opts(PwHashKind::Pbkdf2, Some(3), None, None)
+            .pwhash()
+            .is_err());
+        assert!(opts(PwHashKind::Pbkdf2, None, Some(65536), None)
+            .pwhash()
+            .is_err());
+    }
+
+    #[test]
+    fn benchmark_scaling() {
+        let ms = Duration::from_millis;
+        assert_eq!(4, scale(1, ms(250), ms(1000)));
+        assert_eq!(100_000, scale(10_000, ms(100), ms(1000)));
+        assert_eq!(5_000, scale(10_000, ms(200), ms(100)));
+        // An unmeasurably fast sample counts as one microsecond
+        assert_eq!(1_000_000, scale(1, Duration::from_micros(0), ms(1000)));
+    }
+}
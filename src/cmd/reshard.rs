@@ -0,0 +1,179 @@
+use crate::{
+    cmd::*,
+    format::Format,
+    result::{anyhow, Result},
+    wallet::Wallet,
+};
+
+#[derive(Debug, StructOpt)]
+/// Reshard a sharded wallet into a new set of shards.
+///
+/// At least K shards of the existing wallet must be given. The wallet is
+/// decrypted and encrypted again with a freshly generated sharing key, so
+/// shards of the old set can not be combined with shards of the new set. The
+/// number of shards and the number of shards required to recover the key can
+/// be changed in the process. The same password is used to decrypt the old
+/// and encrypt the new shards.
+pub struct Cmd {
+    #[structopt(short, long)]
+    /// Base file name to store the new shards in. Defaults to the base name
+    /// of the first given shard file
+    output: Option<PathBuf>,
+
+    #[structopt(long)]
+    /// Overwrite existing files. Existing shard files numbered above the new
+    /// number of shards are removed
+    force: bool,
+
+    #[structopt(short = "n", long = "shards")]
+    /// Number of shards to break the key into. Defaults to the number of
+    /// shards of the existing wallet
+    key_share_count: Option<u8>,
+
+    #[structopt(short = "k", long = "required-shards")]
+    /// Number of shards required to recover the key. Defaults to the number
+    /// required by the existing wallet
+    recovery_threshold: Option<u8>,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let output = match &self.output {
+            Some(output) => output.clone(),
+            None => shard_base_filename(
+                opts.files
+                    .first()
+                    .ok_or_else(|| anyhow!("At least one wallet file expected"))?,
+            ),
+        };
+        let password = get_password(&opts.password, false)?;
+        let keyfile = opts.keyfile()?;
+        let wallet = load_wallet(opts.files)?;
+        let (key_share_count, recovery_threshold, pwhash) = match &wallet.format {
+            Format::Sharded(format) => (
+                self.key_share_count.unwrap_or(format.key_share_count),
+                self.recovery_threshold.unwrap_or(format.recovery_threshold),
+                format.pwhash.with_new_salt(),
+            ),
+            _ => bail!("Wallet is not sharded. Use \"upgrade sharded\" to shard a basic wallet"),
+        };
+        if recovery_threshold == 0 || recovery_threshold > key_share_count {
+            bail!(
+                "Required shards ({}) must be between 1 and the number of shards ({})",
+                recovery_threshold,
+                key_share_count
+            );
+        }
+        let keypair = wallet.decrypt(password.as_bytes(), keyfile.as_ref())?;
+        let new_wallet = reshard(
+            &wallet,
+            &keypair,
+            password.as_bytes(),
+            keyfile.as_ref(),
+            Format::sharded(key_share_count, recovery_threshold, pwhash),
+        )?;
+
+        // Shard files numbered above the new shard count are left over from
+        // an older, larger set. They are removed so they do not end up mixed
+        // into the new set.
+        let filenames = shard_filenames(&output, u8::MAX);
+        let (filenames, stale_filenames) = filenames.split_at(key_share_count as usize);
+        let stale_filenames: Vec<&PathBuf> = stale_filenames
+            .iter()
+            .filter(|filename| filename.exists())
+            .collect();
+        if !self.force {
+            if let Some(filename) = filenames
+                .iter()
+                .chain(stale_filenames.iter().copied())
+                .find(|filename| filename.exists())
+            {
+                bail!(
+                    "{} already exists. Use --force to overwrite",
+                    filename.display()
+                );
+            }
+        }
+        write_wallets_atomic(filenames, &new_wallet.shards()?)?;
+        for filename in stale_filenames {
+            fs::remove_file(filename)?;
+        }
+        verify::print_result(&new_wallet, true, opts.format)
+    }
+}
+
+/// Encrypts the decrypted key of the given wallet again in the given sharded
+/// format.
+/// A new sharing key is generated, so the new shards do not combine with the
+/// shards of the given wallet.
+fn reshard(
+    wallet: &Wallet,
+    keypair: &Keypair,
+    password: &[u8],
+    keyfile: Option<&Keyfile>,
+    format: Format,
+) -> Result<Wallet> {
+    Wallet::encrypt(keypair, password, keyfile, format, wallet.metadata.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{keypair::Keypair, pwhash::PwHash};
+
+    /// Combines the given shards into a single wallet
+    fn combine(shards: Vec<Wallet>) -> Result<Wallet> {
+        let mut shards = shards.into_iter();
+        let mut wallet = shards.next().expect("shard");
+        for shard in shards {
+            wallet.absorb_shard(&shard)?;
+        }
+        Ok(wallet)
+    }
+
+    #[test]
+    fn reshard_wallet() {
+        let password = b"password";
+        let keypair = Keypair::default();
+        let format = Format::sharded(5, 3, PwHash::pbkdf2(1000));
+        let wallet = Wallet::encrypt(&keypair, password, None, format, None).expect("wallet");
+        let old_shards: Vec<Wallet> = wallet
+            .shards()
+            .expect("shards")
+            .into_iter()
+            .take(3)
+            .collect();
+        let old_wallet = combine(old_shards).expect("old wallet");
+
+        let format = Format::sharded(3, 2, PwHash::pbkdf2(1000));
+        let old_keypair = old_wallet.decrypt(password, None).expect("decrypt");
+        assert_eq!(keypair, old_keypair);
+        let new_wallet =
+            reshard(&old_wallet, &old_keypair, password, None, format).expect("reshard");
+        let new_shards = new_wallet.shards().expect("shards");
+        assert_eq!(3, new_shards.len());
+        let two_new = combine(new_shards.into_iter().skip(1).collect()).expect("new wallet");
+        assert_eq!(keypair, two_new.decrypt(password, None).expect("decrypt"));
+
+        // Shards of a reshard with the same shape do not combine either
+        let format = Format::sharded(5, 3, PwHash::pbkdf2(1000));
+        let same_shape =
+            reshard(&old_wallet, &old_keypair, password, None, format).expect("reshard");
+        let mut old_shards = wallet.shards().expect("shards").into_iter();
+        let mut new_shards = same_shape.shards().expect("shards").into_iter();
+        let mixed = combine(vec![
+            new_shards.next().expect("shard"),
+            old_shards.nth(1).expect("shard"),
+            old_shards.nth(1).expect("shard"),
+        ])
+        .expect("mixed wallet");
+        assert!(mixed.decrypt(password, None).is_err());
+
+        // Shards of different shapes are rejected outright
+        let mixed = combine(vec![
+            new_wallet.shards().expect("shards").remove(0),
+            wallet.shards().expect("shards").remove(0),
+        ]);
+        assert!(mixed.is_err());
+    }
+}
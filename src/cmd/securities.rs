@@ -16,6 +16,7 @@ pub enum Cmd {
 /// Transfer security tokens to the given target account
 pub struct Transfer {
     /// The address of the recipient of the security tokens
+    #[structopt(parse(try_from_str = parse_address))]
     payee: PublicKey,
 
     /// The number of security tokens to transfer
@@ -36,16 +37,16 @@ impl Cmd {
 
 impl Transfer {
     pub async fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
 
-        let client = Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = api_client(&opts.config, wallet.public_key.network);
 
-        let keypair = wallet.decrypt(password.as_bytes())?;
-        let account = accounts::get(&client, &keypair.public_key().to_string()).await?;
+        let signer = wallet_signer(&wallet, &opts.password, keyfile.as_ref())?;
+        let account = accounts::get(&client, &signer.public_key().to_string()).await?;
 
         let mut txn = BlockchainTxnSecurityExchangeV1 {
-            payer: keypair.public_key().into(),
+            payer: signer.public_key().into(),
             payee: self.payee.to_vec(),
             amount: u64::from(self.amount),
             nonce: account.speculative_sec_nonce + 1,
@@ -53,7 +54,7 @@ impl Transfer {
             signature: vec![],
         };
         txn.fee = txn.txn_fee(&get_txn_fees(&client).await?)?;
-        txn.signature = txn.sign(&keypair)?;
+        txn.signature = txn.sign(&signer)?;
 
         let envelope = txn.in_envelope();
         let status = maybe_submit_txn(self.commit, &client, &envelope).await?;
@@ -67,12 +68,13 @@ fn print_txn(
     status: &Option<PendingTxnStatus>,
     format: OutputFormat,
 ) -> Result {
-    let payee = PublicKey::from_bytes(&txn.payee)?.to_string();
+    let payee = PublicKey::from_bytes(&txn.payee)?;
     match format {
         OutputFormat::Table => {
+            let contacts = address_book();
             ptable!(
                 ["Key", "Value"],
-                ["Payee", payee],
+                ["Payee", contacts.display(&payee)],
                 ["Amount (HST)", Hst::from(txn.amount)],
                 ["Fee (DC)", txn.fee],
                 ["Nonce", txn.nonce],
@@ -82,7 +84,7 @@ fn print_txn(
         }
         OutputFormat::Json => {
             let table = json!({
-                "payee": payee,
+                "payee": payee.to_string(),
                 "amount": txn.amount,
                     "fee": txn.fee,
              "nonce": txn.nonce,
@@ -0,0 +1,167 @@
+use crate::{
+    cmd::{pwhash::PwHashOpts, *},
+    format::{KeySlot, MAX_KEY_SLOTS},
+    result::Result,
+    wallet::Wallet,
+};
+use prettytable::Table;
+use serde_json::json;
+
+#[derive(Debug, StructOpt)]
+/// Manage the key slots of a key slot wallet.
+///
+/// Each key slot unlocks the wallet with its own password. Slots can be
+/// added and removed without changing the passwords of the other slots. Use
+/// "upgrade slots" to convert an existing wallet to a key slot wallet.
+pub enum Cmd {
+    List(List),
+    Add(Add),
+    Remove(Remove),
+}
+
+#[derive(Debug, StructOpt)]
+/// List the key slots of a wallet
+pub struct List {}
+
+#[derive(Debug, StructOpt)]
+/// Add a key slot with a new password. The wallet is unlocked with the
+/// password of an existing slot.
+///
+/// Unlocking a key slot wallet tries its slots in turn, each with a full
+/// password hash, so every slot adds the unlock time of one password. A
+/// wallet can have at most 8 slots.
+pub struct Add {
+    #[structopt(short, long)]
+    /// Output file to store the wallet in. Defaults to the given wallet file
+    output: Option<PathBuf>,
+
+    #[structopt(flatten)]
+    pwhash: PwHashOpts,
+}
+
+#[derive(Debug, StructOpt)]
+/// Remove a key slot. The wallet is unlocked with the password of any slot,
+/// including the one being removed. The last slot of a wallet can not be
+/// removed.
+///
+/// Removing a slot does not change the key the wallet is encrypted with.
+/// Anyone who kept a copy of the wallet from before the slot was removed can
+/// still unlock that copy with the removed password.
+pub struct Remove {
+    /// Index of the slot to remove, as shown by "slots list"
+    slot: usize,
+
+    #[structopt(short, long)]
+    /// Output file to store the wallet in. Defaults to the given wallet file
+    output: Option<PathBuf>,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::List(cmd) => cmd.run(opts).await,
+            Cmd::Add(cmd) => cmd.run(opts).await,
+            Cmd::Remove(cmd) => cmd.run(opts).await,
+        }
+    }
+}
+
+impl List {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let wallet = load_wallet(opts.files)?;
+        print_slots(&wallet, opts.format)
+    }
+}
+
+impl Add {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let output = output_file(&self.output, &opts.files)?;
+        let password = get_password(&opts.password, false)?;
+        let keyfile = opts.keyfile()?;
+        let mut wallet = load_wallet(opts.files)?;
+        let format = wallet.mut_slots_format()?;
+        if format.key_slots.len() >= MAX_KEY_SLOTS {
+            bail!(
+                "Wallet already has the maximum of {} key slots",
+                MAX_KEY_SLOTS
+            );
+        }
+        let (_, data_key) = format.unseal(password.as_bytes(), keyfile.as_ref())?;
+
+        let new_password = get_new_password()?;
+        if let Ok((index, _)) = format.unseal(new_password.as_bytes(), keyfile.as_ref()) {
+            bail!("New password already unlocks slot {}", index);
+        }
+        let slot = KeySlot::seal(
+            &data_key,
+            new_password.as_bytes(),
+            keyfile.as_ref(),
+            self.pwhash.pwhash()?,
+        )?;
+        format.key_slots.push(slot);
+        write_wallets_atomic(&[output], std::slice::from_ref(&*wallet))?;
+        print_slots(&wallet, opts.format)
+    }
+}
+
+impl Remove {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let output = output_file(&self.output, &opts.files)?;
+        let password = get_password(&opts.password, false)?;
+        let keyfile = opts.keyfile()?;
+        let mut wallet = load_wallet(opts.files)?;
+        let format = wallet.mut_slots_format()?;
+        if self.slot >= format.key_slots.len() {
+            bail!("Wallet has no key slot {}", self.slot);
+        }
+        if format.key_slots.len() == 1 {
+            bail!("Can not remove the last key slot of a wallet");
+        }
+        format.unseal(password.as_bytes(), keyfile.as_ref())?;
+        format.key_slots.remove(self.slot);
+        format.pwhash = format.key_slots[0].pwhash;
+        write_wallets_atomic(&[output], std::slice::from_ref(&*wallet))?;
+        print_slots(&wallet, opts.format)
+    }
+}
+
+fn output_file(output: &Option<PathBuf>, files: &[PathBuf]) -> Result<PathBuf> {
+    match output {
+        Some(output) => Ok(output.clone()),
+        None => match files {
+            [file] => Ok(file.clone()),
+            _ => bail!("Exactly one wallet file expected"),
+        },
+    }
+}
+
+fn print_slots(wallet: &Wallet, format: OutputFormat) -> Result {
+    let slots = &wallet.slots_format()?.key_slots;
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.add_row(row!["Slot", "PwHash"]);
+            for (index, slot) in slots.iter().enumerate() {
+                table.add_row(row![index, slot.pwhash]);
+            }
+            print_table(&table)
+        }
+        OutputFormat::Json => {
+            let slots: Vec<serde_json::Value> = slots
+                .iter()
+                .enumerate()
+                .map(|(index, slot)| {
+                    json!({
+                        "slot": index,
+                        "pwhash": slot.pwhash.to_string(),
+                    })
+                })
+                .collect();
+            let table = json!({
+                "address": wallet.address()?,
+                "slots": slots,
+            });
+            print_json(&table)
+        }
+    }
+}
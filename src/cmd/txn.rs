@@ -0,0 +1,284 @@
+use crate::{
+    cmd::*,
+    result::{bail, Result},
+    traits::{ToJson, TxnSign, TxnSignature, TxnSignatures, B64},
+};
+use prettytable::Table;
+use serde_json::json;
+
+#[derive(Debug, StructOpt)]
+/// Inspect and sign transactions
+pub enum Cmd {
+    Decode(Decode),
+    Sign(Sign),
+}
+
+#[derive(Debug, StructOpt)]
+/// Decode a base64 encoded transaction, for example one handed over by a
+/// hotspot maker or counterparty to sign, and show its content, locally
+/// computed hash and which signatures are present or missing.
+pub struct Decode {
+    /// Base64 encoded transaction to decode. If no transaction is given stdin
+    /// is read for the transaction.
+    #[structopt(name = "TRANSACTION")]
+    txn: Option<Transaction>,
+}
+
+#[derive(Debug, StructOpt)]
+/// Sign a base64 encoded transaction that was built elsewhere. The wallet key
+/// signs every role it has in the transaction, like payer, owner, buyer,
+/// seller or gateway, and the updated transaction is printed. Signing fails
+/// if the wallet has no role in the transaction.
+pub struct Sign {
+    /// Base64 encoded transaction to sign. If no transaction is given stdin is
+    /// read for the transaction.
+    #[structopt(name = "TRANSACTION")]
+    txn: Option<Transaction>,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::Decode(cmd) => cmd.run(opts).await,
+            Cmd::Sign(cmd) => cmd.run(opts).await,
+        }
+    }
+}
+
+/// The content, hash and signatures of a decoded transaction
+struct Decoded {
+    json: serde_json::Value,
+    hash: String,
+    signatures: Vec<TxnSignature>,
+}
+
+impl Decoded {
+    fn new<T: ToJson + TxnSign + TxnSignatures>(txn: &T) -> Result<Self> {
+        Ok(Self {
+            json: txn.to_json()?,
+            hash: txn.hash()?.to_b64_url()?,
+            signatures: txn.signatures()?,
+        })
+    }
+
+    fn to_json(&self, envelope: &BlockchainTxn) -> Result<serde_json::Value> {
+        let mut table = self.json.clone();
+        table["hash"] = self.hash.clone().into();
+        table["signatures"] = self
+            .signatures
+            .iter()
+            .map(|signature| {
+                json!({
+                    "field": signature.field,
+                    "signer": signature.signer.as_ref().map(|s| s.to_string()),
+                    "status": signature.status.to_string(),
+                })
+            })
+            .collect();
+        table["txn"] = envelope.to_b64()?.into();
+        Ok(table)
+    }
+}
+
+/// Evaluates the body with the transaction in the given envelope bound to
+/// the name, for every supported transaction type
+macro_rules! with_txn {
+    ($envelope_txn:expr, $txn:ident => $body:expr) => {
+        with_txn!(
+            $envelope_txn,
+            $txn => $body,
+            PriceOracleSubmission,
+            Oui,
+            CreateHtlc,
+            RedeemHtlc,
+            Payment,
+            PaymentV2,
+            SecurityExchange,
+            TokenBurn,
+            AddGateway,
+            AssertLocation,
+            AssertLocationV2,
+            Vars,
+            TransferHotspot,
+            StakeValidator,
+            UnstakeValidator,
+            TransferValStake,
+            Routing
+        )
+    };
+    ($envelope_txn:expr, $txn:ident => $body:expr, $( $kind:ident ),+ ) => {
+        match $envelope_txn {
+            $(Some(Txn::$kind($txn)) => $body,)+
+            Some(_) => bail!("Unsupported transaction type"),
+            None => bail!("Empty transaction"),
+        }
+    };
+}
+
+impl Decode {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let envelope = read_txn(&self.txn, &opts.password)?;
+        let decoded = with_txn!(&envelope.txn, txn => Decoded::new(txn))?;
+        print_txn(&decoded, &envelope, opts.format)
+    }
+}
+
+impl Sign {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let mut envelope = read_txn(&self.txn, &opts.password)?;
+        let keyfile = opts.keyfile()?;
+        let wallet = load_wallet(opts.files)?;
+
+        // Check for a role before asking for the password
+        let signatures = with_txn!(&envelope.txn, txn => txn.signatures())?;
+        if !signatures
+            .iter()
+            .any(|signature| signature.signer.as_ref() == Some(&wallet.public_key))
+        {
+            bail!(
+                "Wallet {} has no role in this transaction",
+                wallet.public_key
+            );
+        }
+        let signer = wallet_signer(&wallet, &opts.password, keyfile.as_ref())?;
+        let signed = with_txn!(
+            &mut envelope.txn,
+            txn => txn.sign_for(&wallet.public_key, &signer)
+        )?;
+        if signed.is_empty() {
+            bail!("Wallet {} can not sign this transaction", wallet.public_key);
+        }
+
+        let decoded = with_txn!(&envelope.txn, txn => Decoded::new(txn))?;
+        match opts.format {
+            OutputFormat::Table => {
+                print_txn(&decoded, &envelope, opts.format)?;
+                println!("\nSigned {}:\n{}", signed.join(", "), envelope.to_b64()?);
+                Ok(())
+            }
+            OutputFormat::Json => {
+                let mut table = decoded.to_json(&envelope)?;
+                table["signed"] = signed.into();
+                print_json(&table)
+            }
+        }
+    }
+}
+
+fn print_txn(decoded: &Decoded, envelope: &BlockchainTxn, format: OutputFormat) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let kind = decoded.json["type"].as_str().unwrap_or_default();
+            let mut table = Table::new();
+            table.add_row(row!["Key", "Value"]);
+            if let serde_json::Value::Object(map) = &decoded.json {
+                for (key, value) in map {
+                    // Signatures are shown in their own table below
+                    if key == "type" || key.ends_with("signature") || key.contains("proof") {
+                        continue;
+                    }
+                    table.add_row(row![label(kind, key), value_str(kind, value)]);
+                }
+            }
+            table.add_row(row!["Hash", decoded.hash]);
+            print_table(&table)?;
+
+            let contacts = address_book();
+            let mut table = Table::new();
+            table.add_row(row!["Signature", "Signer", "Status"]);
+            for signature in &decoded.signatures {
+                table.add_row(row![
+                    signature.field,
+                    signature
+                        .signer
+                        .as_ref()
+                        .map_or_else(|| "none".to_string(), |s| contacts.display(s)),
+                    signature.status
+                ]);
+            }
+            print_table(&table)
+        }
+        OutputFormat::Json => print_json(&decoded.to_json(envelope)?),
+    }
+}
+
+/// Returns the table label for a transaction field, with the unit of
+/// amounts and fees like the other transaction tables
+fn label(kind: &str, key: &str) -> String {
+    match unit(kind, key) {
+        Some(unit) => format!("{} ({})", key, unit),
+        None => key.to_string(),
+    }
+}
+
+fn unit(kind: &str, key: &str) -> Option<&'static str> {
+    match key {
+        "fee" | "staking_fee" => Some("DC"),
+        "amount" if kind == "security_exchange_v1" => Some("HST"),
+        "amount" | "amount_to_seller" | "stake" | "stake_amount" | "payment_amount" => Some("HNT"),
+        "price" => Some("USD"),
+        _ => None,
+    }
+}
+
+/// Renders a json value for a table cell. Lists are shown one entry per
+/// line and nested objects as key=value pairs.
+fn value_str(kind: &str, value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => "none".to_string(),
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(entries) => entries
+            .iter()
+            .map(|entry| value_str(kind, entry))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| format!("{}={}", label(kind, key), value_str(kind, value)))
+            .collect::<Vec<String>>()
+            .join(", "),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::Keypair;
+
+    #[test]
+    fn units() {
+        assert_eq!("fee (DC)", label("payment_v2", "fee"));
+        assert_eq!("staking_fee (DC)", label("add_gateway_v1", "staking_fee"));
+        assert_eq!("amount (HNT)", label("payment_v2", "amount"));
+        assert_eq!("amount (HST)", label("security_exchange_v1", "amount"));
+        assert_eq!("nonce", label("payment_v2", "nonce"));
+    }
+
+    #[test]
+    fn decoded_hash() {
+        let payer = Keypair::default();
+        let mut txn = BlockchainTxnTokenBurnV1 {
+            payer: payer.public_key().to_vec(),
+            payee: Keypair::default().public_key().to_vec(),
+            amount: 100_000_000,
+            memo: 1,
+            nonce: 1,
+            fee: 0,
+            signature: vec![],
+        };
+        let hash = txn.hash().expect("hash").to_b64_url().expect("b64");
+        txn.signature = txn.sign(&payer).expect("sign");
+        let envelope = BlockchainTxn {
+            txn: Some(Txn::TokenBurn(txn.clone())),
+        };
+        let json = Decoded::new(&txn)
+            .expect("decoded")
+            .to_json(&envelope)
+            .expect("json");
+        // The hash leaves out the signature
+        assert_eq!(json!(hash), json["hash"]);
+        assert_eq!(json!(envelope.to_b64().expect("b64")), json["txn"]);
+        assert_eq!("valid", json["signatures"][0]["status"]);
+    }
+}
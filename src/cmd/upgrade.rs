@@ -1,7 +1,6 @@
 use crate::{
-    cmd::*,
-    format::{self, Format},
-    pwhash::PwHash,
+    cmd::{pwhash::PwHashOpts, *},
+    format::{self, Format, Keyfile},
     result::Result,
     wallet::Wallet,
 };
@@ -14,6 +13,7 @@ use std::path::PathBuf;
 pub enum Cmd {
     Basic(Basic),
     Sharded(Sharded),
+    Slots(Slots),
 }
 
 #[derive(Debug, StructOpt)]
@@ -26,6 +26,15 @@ pub struct Basic {
     #[structopt(long)]
     /// Overwrite an existing file
     force: bool,
+
+    #[structopt(flatten)]
+    pwhash: PwHashOpts,
+
+    #[structopt(flatten)]
+    metadata: MetadataOpts,
+
+    #[structopt(flatten)]
+    keyfile: KeyfileOpts,
 }
 
 #[derive(Debug, StructOpt)]
@@ -39,6 +48,15 @@ pub struct Sharded {
     /// Overwrite an existing file
     force: bool,
 
+    #[structopt(flatten)]
+    pwhash: PwHashOpts,
+
+    #[structopt(flatten)]
+    metadata: MetadataOpts,
+
+    #[structopt(flatten)]
+    keyfile: KeyfileOpts,
+
     #[structopt(short = "n", long = "shards", default_value = "5")]
     /// Number of shards to break the key into
     key_share_count: u8,
@@ -48,25 +66,81 @@ pub struct Sharded {
     recovery_threshold: u8,
 }
 
+#[derive(Debug, StructOpt)]
+/// Upgrade to a key slot wallet. The current password unlocks the first
+/// key slot. Use the `slots` command to add more.
+pub struct Slots {
+    #[structopt(short, long, default_value = "wallet.key")]
+    /// Output file to store the key in
+    output: PathBuf,
+
+    #[structopt(long)]
+    /// Overwrite an existing file
+    force: bool,
+
+    #[structopt(flatten)]
+    pwhash: PwHashOpts,
+
+    #[structopt(flatten)]
+    metadata: MetadataOpts,
+
+    #[structopt(flatten)]
+    keyfile: KeyfileOpts,
+}
+
+/// Options to change the keyfile of an upgraded wallet
+#[derive(Debug, StructOpt)]
+pub struct KeyfileOpts {
+    #[structopt(long)]
+    /// Keyfile to protect the upgraded wallet with. Defaults to the keyfile
+    /// of the existing wallet, if any
+    new_keyfile: Option<PathBuf>,
+
+    #[structopt(long, conflicts_with = "new_keyfile")]
+    /// Do not protect the upgraded wallet with a keyfile
+    no_keyfile: bool,
+}
+
+impl KeyfileOpts {
+    /// The keyfile for the upgraded wallet given the keyfile of the
+    /// existing wallet
+    fn keyfile(&self, keyfile: Option<Keyfile>) -> Result<Option<Keyfile>> {
+        match &self.new_keyfile {
+            Some(path) => Ok(Some(Keyfile::read(path)?)),
+            None if self.no_keyfile => Ok(None),
+            None => Ok(keyfile),
+        }
+    }
+}
+
 impl Cmd {
     pub async fn run(&self, opts: Opts) -> Result {
         match self {
             Cmd::Basic(cmd) => cmd.run(opts).await,
             Cmd::Sharded(cmd) => cmd.run(opts).await,
+            Cmd::Slots(cmd) => cmd.run(opts).await,
         }
     }
 }
 
 impl Basic {
     pub async fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
+        let password = get_password(&opts.password, false)?;
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let keypair = wallet.decrypt(password.as_bytes(), keyfile.as_ref())?;
+        let new_keyfile = self.keyfile.keyfile(keyfile)?;
 
         let format = format::Basic {
-            pwhash: PwHash::argon2id13_default(),
+            pwhash: self.pwhash.pwhash()?,
         };
-        let new_wallet = Wallet::encrypt(&keypair, password.as_bytes(), Format::Basic(format))?;
+        let new_wallet = Wallet::encrypt(
+            &keypair,
+            password.as_bytes(),
+            new_keyfile.as_ref(),
+            Format::Basic(format),
+            self.metadata.update_metadata(wallet.metadata.clone()),
+        )?;
         let mut writer = open_output_file(&self.output, !self.force)?;
         new_wallet.write(&mut writer)?;
         verify::print_result(&new_wallet, true, opts.format)
@@ -75,26 +149,52 @@ impl Basic {
 
 impl Sharded {
     pub async fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
+        let password = get_password(&opts.password, false)?;
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let keypair = wallet.decrypt(password.as_bytes(), keyfile.as_ref())?;
+        let new_keyfile = self.keyfile.keyfile(keyfile)?;
 
         let format = format::Sharded {
             key_share_count: self.key_share_count,
             recovery_threshold: self.recovery_threshold,
-            pwhash: PwHash::argon2id13_default(),
+            pwhash: self.pwhash.pwhash()?,
             key_shares: vec![],
         };
-        let new_wallet = Wallet::encrypt(&keypair, password.as_bytes(), Format::Sharded(format))?;
-
-        let extension = get_file_extension(&self.output);
-        for (i, shard) in new_wallet.shards()?.iter().enumerate() {
-            let mut filename = self.output.clone();
-            let share_extension = format!("{}.{}", extension, (i + 1).to_string());
-            filename.set_extension(share_extension);
-            let mut writer = open_output_file(&filename, !self.force)?;
+        let new_wallet = Wallet::encrypt(
+            &keypair,
+            password.as_bytes(),
+            new_keyfile.as_ref(),
+            Format::Sharded(format),
+            self.metadata.update_metadata(wallet.metadata.clone()),
+        )?;
+
+        let filenames = shard_filenames(&self.output, self.key_share_count);
+        for (filename, shard) in filenames.iter().zip(new_wallet.shards()?) {
+            let mut writer = open_output_file(filename, !self.force)?;
             shard.write(&mut writer)?;
         }
         verify::print_result(&new_wallet, true, opts.format)
     }
 }
+
+impl Slots {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let password = get_password(&opts.password, false)?;
+        let keyfile = opts.keyfile()?;
+        let wallet = load_wallet(opts.files)?;
+        let keypair = wallet.decrypt(password.as_bytes(), keyfile.as_ref())?;
+        let new_keyfile = self.keyfile.keyfile(keyfile)?;
+
+        let new_wallet = Wallet::encrypt(
+            &keypair,
+            password.as_bytes(),
+            new_keyfile.as_ref(),
+            Format::slots(self.pwhash.pwhash()?),
+            self.metadata.update_metadata(wallet.metadata.clone()),
+        )?;
+        let mut writer = open_output_file(&self.output, !self.force)?;
+        new_wallet.write(&mut writer)?;
+        verify::print_result(&new_wallet, true, opts.format)
+    }
+}
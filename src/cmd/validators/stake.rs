@@ -1,6 +1,5 @@
 use crate::{
     cmd::*,
-    keypair::Keypair,
     result::Result,
     traits::{TxnEnvelope, TxnFee, TxnSign},
 };
@@ -62,11 +61,11 @@ impl Cmd {
     pub async fn run(&self, opts: Opts) -> Result {
         let validators = self.collect_validators()?;
 
-        let password = get_password(false)?;
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let signer = wallet_signer(&wallet, &opts.password, keyfile.as_ref())?;
 
-        let client = helium_api::Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = api_client(&opts.config, wallet.public_key.network);
         let fee_config = if self.fee().is_none() {
             Some(get_txn_fees(&client).await?)
         } else {
@@ -81,7 +80,7 @@ impl Cmd {
                     wallet.public_key.network
                 )
             }
-            let txn = self.mk_txn(&keypair, &fee_config, &validator)?;
+            let txn = self.mk_txn(&signer, &fee_config, &validator)?;
             let envelope = txn.in_envelope();
             let status = maybe_submit_txn(self.commit(), &client, &envelope).await?;
             print_txn(&envelope, &txn, &status, &opts.format)?
@@ -91,13 +90,13 @@ impl Cmd {
 
     fn mk_txn(
         &self,
-        keypair: &Keypair,
+        signer: &WalletSigner,
         fee_config: &Option<TxnFeeConfig>,
         validator: &Validator,
     ) -> Result<BlockchainTxnStakeValidatorV1> {
         let mut txn = BlockchainTxnStakeValidatorV1 {
             address: validator.address.to_vec(),
-            owner: keypair.public_key().to_vec(),
+            owner: signer.public_key().to_vec(),
             stake: u64::from(validator.stake),
             fee: 0,
             owner_signature: vec![],
@@ -107,7 +106,7 @@ impl Cmd {
         } else {
             txn.txn_fee(fee_config.as_ref().unwrap())?
         };
-        txn.owner_signature = txn.sign(keypair)?;
+        txn.owner_signature = txn.sign(signer)?;
         Ok(txn)
     }
 
@@ -174,6 +173,8 @@ fn print_txn(
 #[derive(Debug, Deserialize, StructOpt, Clone)]
 pub struct Validator {
     /// The validator address to stake
+    #[serde(deserialize_with = "deserialize_address")]
+    #[structopt(parse(try_from_str = parse_address))]
     address: PublicKey,
     /// The amount of HNT to stake
     stake: Hnt,
@@ -18,22 +18,22 @@ pub enum Cmd {
 /// is assumed to be that/those owner(s).
 pub struct Create {
     /// The validator to transfer the stake from
-    #[structopt(long)]
+    #[structopt(long, parse(try_from_str = parse_address))]
     old_address: PublicKey,
 
     /// The validator to transfer the stake to
-    #[structopt(long)]
+    #[structopt(long, parse(try_from_str = parse_address))]
     new_address: PublicKey,
 
     /// The new owner of the transferred validator and stake. If not present
     /// the new owner is assumed to be the same as the current owner as defined
     /// on the blockchain.
-    #[structopt(long)]
+    #[structopt(long, parse(try_from_str = parse_address))]
     new_owner: Option<PublicKey>,
 
     /// The current (old) owner of the transferred validator and stake. If not present
     /// the old owner is set to the public key of the given wallet.
-    #[structopt(long)]
+    #[structopt(long, parse(try_from_str = parse_address))]
     old_owner: Option<PublicKey>,
 
     /// The payment from new owner to old owner as part of the the stake transfer
@@ -59,10 +59,9 @@ pub struct Create {
 /// owner or the old owner if the owner keys match the public key of the given
 /// wallet.
 pub struct Accept {
-    /// Base64 encoded transaction to sign. If no transaction if given
-    /// stdin is read for the transaction. Note that the stdin feature
-    /// only works if the wallet password is set in the
-    /// HELIUM_WALLET_PASSWORD environment variable
+    /// Base64 encoded transaction to sign. If no transaction is given stdin is
+    /// read for the transaction. The wallet password can then be given with
+    /// any password source other than file descriptor 0
     #[structopt(name = "TRANSACTION")]
     txn: Option<Transaction>,
 
@@ -82,11 +81,11 @@ impl Cmd {
 
 impl Create {
     pub async fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let signer = wallet_signer(&wallet, &opts.password, keyfile.as_ref())?;
 
-        let client = helium_api::Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = api_client(&opts.config, wallet.public_key.network);
 
         let old_owner = self.old_owner.as_ref().unwrap_or(&wallet.public_key);
 
@@ -120,11 +119,11 @@ impl Create {
             txn.txn_fee(&get_txn_fees(&client).await?)?
         };
         if old_owner == &wallet.public_key {
-            txn.old_owner_signature = txn.sign(&keypair)?;
+            txn.old_owner_signature = txn.sign(&signer)?;
         }
         if let Some(owner) = &self.new_owner {
             if owner == &wallet.public_key {
-                txn.new_owner_signature = txn.sign(&keypair)?;
+                txn.new_owner_signature = txn.sign(&signer)?;
             }
         }
 
@@ -136,22 +135,25 @@ impl Create {
 
 impl Accept {
     pub async fn run(&self, opts: Opts) -> Result {
-        let mut txn = BlockchainTxnTransferValidatorStakeV1::from_envelope(&read_txn(&self.txn)?)?;
+        let mut txn = BlockchainTxnTransferValidatorStakeV1::from_envelope(&read_txn(
+            &self.txn,
+            &opts.password,
+        )?)?;
 
-        let password = get_password(false)?;
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let signer = wallet_signer(&wallet, &opts.password, keyfile.as_ref())?;
 
         if !txn.old_owner.is_empty() && PublicKey::from_bytes(&txn.old_owner)? == wallet.public_key
         {
-            txn.old_owner_signature = txn.sign(&keypair)?;
+            txn.old_owner_signature = txn.sign(&signer)?;
         }
         if !txn.new_owner.is_empty() && PublicKey::from_bytes(&txn.new_owner)? == wallet.public_key
         {
-            txn.new_owner_signature = txn.sign(&keypair)?;
+            txn.new_owner_signature = txn.sign(&signer)?;
         }
 
-        let client = helium_api::Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = api_client(&opts.config, wallet.public_key.network);
 
         let envelope = txn.in_envelope();
         let status = maybe_submit_txn(self.commit, &client, &envelope).await?;
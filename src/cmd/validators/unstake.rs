@@ -14,6 +14,7 @@ use crate::{
 /// processing delays.
 pub struct Cmd {
     /// Address of the validator to unstake
+    #[structopt(parse(try_from_str = parse_address))]
     address: PublicKey,
 
     /// The amount of HNT of the original stake
@@ -37,11 +38,11 @@ pub struct Cmd {
 
 impl Cmd {
     pub async fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
+        let keyfile = opts.keyfile()?;
         let wallet = load_wallet(opts.files)?;
-        let keypair = wallet.decrypt(password.as_bytes())?;
+        let signer = wallet_signer(&wallet, &opts.password, keyfile.as_ref())?;
 
-        let client = helium_api::Client::new_with_base_url(api_url(wallet.public_key.network));
+        let client = api_client(&opts.config, wallet.public_key.network);
 
         let mut txn = BlockchainTxnUnstakeValidatorV1 {
             address: self.address.to_vec(),
@@ -65,7 +66,7 @@ impl Cmd {
         } else {
             txn.txn_fee(&get_txn_fees(&client).await?)?
         };
-        txn.owner_signature = txn.sign(&keypair)?;
+        txn.owner_signature = txn.sign(&signer)?;
 
         let envelope = txn.in_envelope();
         let status = maybe_submit_txn(self.commit, &client, &envelope).await?;
@@ -40,7 +40,7 @@ pub struct Create {
     cancel: Vec<String>,
 
     /// Signing keys to set
-    #[structopt(long, name = "key")]
+    #[structopt(long, name = "key", parse(try_from_str = parse_address))]
     key: Vec<PublicKey>,
 
     /// The nonce to use
@@ -70,7 +70,7 @@ impl Current {
     pub async fn run(&self, opts: Opts) -> Result {
         let wallet = load_wallet(opts.files)?;
         let network = self.network.unwrap_or(wallet.public_key.network);
-        let client = Client::new_with_base_url(api_url(network));
+        let client = api_client(&opts.config, network);
         let vars = vars::get(&client).await?;
         print_json(&vars)
     }
@@ -80,7 +80,7 @@ impl Create {
     pub async fn run(&self, opts: Opts) -> Result {
         let wallet = load_wallet(opts.files)?;
         let network = self.network.unwrap_or(wallet.public_key.network);
-        let client = Client::new_with_base_url(api_url(network));
+        let client = api_client(&opts.config, network);
         let vars = vars::get(&client).await?;
 
         let mut txn = BlockchainTxnVarsV1 {
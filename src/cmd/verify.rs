@@ -1,6 +1,11 @@
-use crate::{cmd::*, result::Result, wallet::Wallet};
+use crate::{
+    cmd::*,
+    result::{bail, Result},
+    wallet::{ShardStatus, Wallet},
+};
 use prettytable::{format, Table};
 use serde_json::json;
+use std::path::PathBuf;
 
 /// Verify an encypted wallet
 #[derive(Debug, StructOpt)]
@@ -8,10 +13,97 @@ pub struct Cmd {}
 
 impl Cmd {
     pub async fn run(&self, opts: Opts) -> Result {
-        let password = get_password(false)?;
-        let wallet = load_wallet(opts.files)?;
-        let result = wallet.decrypt(password.as_bytes());
-        print_result(&wallet, result.is_ok(), opts.format)
+        let password = get_password(&opts.password, false)?;
+        let keyfile = opts.keyfile()?;
+        let wallets = read_wallet_files(&opts.files)?;
+        if wallets.len() == 1 && !wallets[0].1.is_sharded() {
+            let wallet = &wallets[0].1;
+            let result = wallet.decrypt(password.as_bytes(), keyfile.as_ref());
+            return print_result(wallet, result.is_ok(), opts.format);
+        }
+
+        // Combine all shards that look like they belong together and check
+        // each of their key shares. Shard files with problems are reported
+        // as such.
+        let problems = shard_file_problems(&wallets);
+        let mut wallet: Option<Wallet> = None;
+        let mut files = vec![];
+        for ((path, shard), problem) in wallets.into_iter().zip(problems) {
+            if problem.is_none() {
+                match &mut wallet {
+                    Some(wallet) => wallet.absorb_shard(&shard)?,
+                    None => wallet = Some(shard),
+                }
+            }
+            files.push((path, problem));
+        }
+        let wallet = match wallet {
+            Some(wallet) => wallet,
+            None => bail!("No usable shard files"),
+        };
+        let statuses = wallet.check_shards(password.as_bytes(), keyfile.as_ref())?;
+        let result = statuses.iter().any(|status| *status == ShardStatus::Good);
+
+        let mut key_shares = wallet
+            .sharded_format()?
+            .key_shares
+            .iter()
+            .map(|key_share| key_share.index())
+            .zip(statuses);
+        let mut shards = vec![];
+        for (path, problem) in files {
+            match problem {
+                Some(problem) => shards.push((path, None, problem)),
+                None => {
+                    let (index, status) = key_shares.next().expect("shard status");
+                    shards.push((path, Some(index), status.to_string()))
+                }
+            }
+        }
+        print_verify(&wallet, result, &shards, opts.format)
+    }
+}
+
+fn print_verify(
+    wallet: &Wallet,
+    result: bool,
+    shards: &[(PathBuf, Option<u8>, String)],
+    format: OutputFormat,
+) -> Result {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row!["File", "Shard", "Status"]);
+            for (path, index, status) in shards {
+                let index = index.map_or_else(|| "unknown".to_string(), |i| i.to_string());
+                table.add_row(row![path.display(), index, status]);
+            }
+            print_table(&table)?;
+            print_result(wallet, result, format)
+        }
+        OutputFormat::Json => {
+            let address = wallet.address().unwrap_or_else(|_| "unknown".to_string());
+            let shards: Vec<serde_json::Value> = shards
+                .iter()
+                .map(|(path, index, status)| {
+                    json!({
+                        "file": path.display().to_string(),
+                        "shard": index,
+                        "status": status,
+                    })
+                })
+                .collect();
+            let table = json!({
+                "address": address,
+                "sharded": wallet.is_sharded(),
+                "verify": result,
+                "pwhash": wallet.pwhash().to_string(),
+                "metadata": metadata_json(&wallet.metadata),
+                "shards": shards,
+            });
+            print_json(&table)
+        }
     }
 }
 
@@ -23,14 +115,23 @@ pub fn print_result(wallet: &Wallet, result: bool, format: OutputFormat) -> Resu
             table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
             table.set_titles(row!["Address", "Sharded", "Verify", "PwHash"]);
             table.add_row(row![address, wallet.is_sharded(), result, wallet.pwhash()]);
-            print_table(&table)
+            print_table(&table)?;
+            if wallet.metadata.is_some() {
+                let mut table = Table::new();
+                table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+                table.set_titles(row!["Metadata", "Value"]);
+                add_metadata_rows(&mut table, &wallet.metadata);
+                print_table(&table)?;
+            }
+            Ok(())
         }
         OutputFormat::Json => {
             let table = json!({
                 "address": address,
                 "sharded": wallet.is_sharded(),
                 "verify": result,
-                "pwhash": wallet.pwhash().to_string()
+                "pwhash": wallet.pwhash().to_string(),
+                "metadata": metadata_json(&wallet.metadata),
             });
             print_json(&table)
         }
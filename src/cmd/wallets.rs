@@ -0,0 +1,184 @@
+use crate::{
+    cmd::*,
+    keyring::Keyring,
+    result::{bail, Result},
+};
+use prettytable::Table;
+use serde_json::json;
+
+#[derive(Debug, StructOpt)]
+/// Manage the named wallets in the keyring.
+///
+/// The keyring is a directory, `helium-wallet/wallets` in the XDG config
+/// directory by default, holding wallets by name. Use the global --wallet
+/// option to use a keyring wallet with any command. Without --wallet or
+/// --file commands use the default wallet of the keyring.
+pub enum Cmd {
+    List(List),
+    Add(Add),
+    Remove(Remove),
+    Rename(Rename),
+    Default(SetDefault),
+}
+
+#[derive(Debug, StructOpt)]
+/// List the wallets in the keyring
+pub struct List {}
+
+#[derive(Debug, StructOpt)]
+/// Copy a wallet file, or a set of shard files, into the keyring
+pub struct Add {
+    /// Name to store the wallet under
+    name: String,
+
+    /// The wallet file or shard files to add
+    #[structopt(required = true)]
+    files: Vec<PathBuf>,
+
+    /// Make the added wallet the default wallet
+    #[structopt(long)]
+    default: bool,
+}
+
+#[derive(Debug, StructOpt)]
+/// Remove a wallet from the keyring. The wallet files are deleted.
+pub struct Remove {
+    /// Name of the wallet to remove
+    name: String,
+
+    /// Confirm that the wallet files should be deleted
+    #[structopt(long)]
+    confirm: bool,
+}
+
+#[derive(Debug, StructOpt)]
+/// Rename a wallet in the keyring
+pub struct Rename {
+    /// Current name of the wallet
+    from: String,
+
+    /// New name of the wallet
+    to: String,
+}
+
+#[derive(Debug, StructOpt)]
+/// Show or set the default wallet
+pub struct SetDefault {
+    /// Name of the wallet to make the default wallet. The current default
+    /// wallet is shown if not given.
+    name: Option<String>,
+}
+
+impl Cmd {
+    pub async fn run(&self, opts: Opts) -> Result {
+        match self {
+            Cmd::List(cmd) => cmd.run(opts).await,
+            Cmd::Add(cmd) => cmd.run(opts).await,
+            Cmd::Remove(cmd) => cmd.run(opts).await,
+            Cmd::Rename(cmd) => cmd.run(opts).await,
+            Cmd::Default(cmd) => cmd.run(opts).await,
+        }
+    }
+}
+
+impl List {
+    pub async fn run(&self, opts: Opts) -> Result {
+        print_wallets(&Keyring::open()?, opts.format)
+    }
+}
+
+impl Add {
+    pub async fn run(&self, opts: Opts) -> Result {
+        // Make sure the files hold a wallet before adding them
+        load_wallet(self.files.clone())?;
+        let keyring = Keyring::open()?;
+        keyring.add(&self.name, &self.files)?;
+        if self.default {
+            keyring.set_default(&self.name)?;
+        }
+        print_wallets(&keyring, opts.format)
+    }
+}
+
+impl Remove {
+    pub async fn run(&self, opts: Opts) -> Result {
+        if !self.confirm {
+            bail!("Removing deletes the wallet files. Use --confirm to remove the wallet");
+        }
+        let keyring = Keyring::open()?;
+        keyring.remove(&self.name)?;
+        print_wallets(&keyring, opts.format)
+    }
+}
+
+impl Rename {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let keyring = Keyring::open()?;
+        keyring.rename(&self.from, &self.to)?;
+        print_wallets(&keyring, opts.format)
+    }
+}
+
+impl SetDefault {
+    pub async fn run(&self, opts: Opts) -> Result {
+        let keyring = Keyring::open()?;
+        if let Some(name) = &self.name {
+            keyring.set_default(name)?;
+        }
+        print_wallets(&keyring, opts.format)
+    }
+}
+
+/// Lists the wallets in the keyring. A wallet that can not be read, for
+/// example a partial set of shards, is listed with its error instead of an
+/// address so it does not hide the other wallets.
+fn print_wallets(keyring: &Keyring, format: OutputFormat) -> Result {
+    let default_name = keyring.default_name()?;
+    let mut wallets = vec![];
+    for name in keyring.names()? {
+        let files = keyring.files(&name)?;
+        let address = load_wallet(files.clone()).and_then(|wallet| wallet.address());
+        let is_default = default_name.as_deref() == Some(name.as_str());
+        wallets.push((name, address, files.len(), is_default));
+    }
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.add_row(row!["Name", "Address", "Files", "Default"]);
+            for (name, address, files, is_default) in wallets {
+                let address = match address {
+                    Ok(address) => address,
+                    Err(err) => format!("error: {}", err),
+                };
+                table.add_row(row![name, address, files, is_default]);
+            }
+            print_table(&table)?;
+            eprintln!("Keyring: {}", keyring.dir().display());
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let wallets: Vec<serde_json::Value> = wallets
+                .into_iter()
+                .map(|(name, address, files, is_default)| match address {
+                    Ok(address) => json!({
+                        "name": name,
+                        "address": address,
+                        "files": files,
+                        "default": is_default,
+                    }),
+                    Err(err) => json!({
+                        "name": name,
+                        "error": err.to_string(),
+                        "files": files,
+                        "default": is_default,
+                    }),
+                })
+                .collect();
+            let table = json!({
+                "keyring": keyring.dir().display().to_string(),
+                "wallets": wallets,
+            });
+            print_json(&table)
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use crate::{
+    keyring,
+    password_source::PasswordSource,
+    result::{anyhow, bail, Result},
+};
+use serde_derive::Deserialize;
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable to override the global config file with
+pub const CONFIG_ENV: &str = "HELIUM_WALLET_CONFIG";
+/// Name of the per-project config file. The nearest one in the current
+/// directory or its parents is used.
+pub const PROJECT_CONFIG_FILE: &str = ".helium-wallet.toml";
+/// The default timeout for API requests in seconds
+pub const DEFAULT_TIMEOUT: u64 = 120;
+
+/// Settings read from the global config file, `helium-wallet/config.toml`
+/// in the XDG config directory, and a per-project `.helium-wallet.toml`.
+/// Project settings override global ones. All settings are optional.
+///
+/// A project file may come from a cloned repository or a shared directory,
+/// so the `api` and `password` settings, which choose the servers that see
+/// and co-sign transactions and may run a command, are only read from the
+/// global config file.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub api: Api,
+    /// The default output format
+    pub format: Option<String>,
+    /// Name of the keyring wallet to use when no wallet is given
+    pub wallet: Option<String>,
+    pub password: Password,
+    /// The config files the settings were read from
+    #[serde(skip)]
+    pub files: Vec<PathBuf>,
+    /// Settings of project config files that were ignored, as the setting
+    /// name and the file it was set in
+    #[serde(skip)]
+    pub ignored: Vec<(String, PathBuf)>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Api {
+    /// Helium API URL for mainnet
+    pub mainnet: Option<String>,
+    /// Helium API URL for testnet
+    pub testnet: Option<String>,
+    /// Staking server URL
+    pub staking: Option<String>,
+    /// Request timeout in seconds
+    pub timeout: Option<u64>,
+}
+
+/// Where to read the wallet password from instead of prompting for it. At
+/// most one source can be given.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Password {
+    /// File whose first line is the password
+    pub file: Option<PathBuf>,
+    /// Shell command whose first line of output is the password
+    pub command: Option<String>,
+}
+
+impl Config {
+    /// Loads and merges the global and project config files. Missing files
+    /// are skipped.
+    pub fn load() -> Result<Self> {
+        let global = match env::var_os(CONFIG_ENV) {
+            Some(path) => Some(PathBuf::from(path)),
+            None => keyring::config_dir()
+                .ok()
+                .map(|dir| dir.join("config.toml")),
+        };
+        let project = project_file(&env::current_dir()?);
+        let mut config = Self::default();
+        if let Some(file_config) = global.as_deref().map(Self::read).transpose()?.flatten() {
+            config = config.merge(file_config);
+        }
+        if let Some(file_config) = project.as_deref().map(Self::read).transpose()?.flatten() {
+            config = config.merge(file_config.project_settings());
+        }
+        Ok(config)
+    }
+
+    /// Reads a single config file, returning None if it does not exist. A
+    /// relative password file is relative to the config file.
+    pub fn read(path: &Path) -> Result<Option<Self>> {
+        let data = match fs::read_to_string(path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let mut config: Self =
+            toml::from_str(&data).map_err(|err| anyhow!("{}: {}", path.display(), err))?;
+        if config.password.file.is_some() && config.password.command.is_some() {
+            bail!(
+                "{}: only one of password.file and password.command can be set",
+                path.display()
+            );
+        }
+        if let (Some(file), Some(dir)) = (&config.password.file, path.parent()) {
+            config.password.file = Some(dir.join(file));
+        }
+        config.files.push(path.to_path_buf());
+        Ok(Some(config))
+    }
+
+    /// Keeps only the settings a project config file may set. The others
+    /// are recorded as ignored.
+    pub fn project_settings(mut self) -> Self {
+        let set = [
+            ("api.mainnet", self.api.mainnet.is_some()),
+            ("api.testnet", self.api.testnet.is_some()),
+            ("api.staking", self.api.staking.is_some()),
+            ("api.timeout", self.api.timeout.is_some()),
+            ("password.file", self.password.file.is_some()),
+            ("password.command", self.password.command.is_some()),
+        ];
+        for path in &self.files {
+            for (name, _) in set.iter().filter(|(_, is_set)| *is_set) {
+                self.ignored.push((name.to_string(), path.clone()));
+            }
+        }
+        self.api = Api::default();
+        self.password = Password::default();
+        self
+    }
+
+    /// Merges the other config into this one. Settings of the other config
+    /// take precedence.
+    pub fn merge(self, other: Self) -> Self {
+        let password = if other.password.is_set() {
+            other.password
+        } else {
+            self.password
+        };
+        Self {
+            api: Api {
+                mainnet: other.api.mainnet.or(self.api.mainnet),
+                testnet: other.api.testnet.or(self.api.testnet),
+                staking: other.api.staking.or(self.api.staking),
+                timeout: other.api.timeout.or(self.api.timeout),
+            },
+            format: other.format.or(self.format),
+            wallet: other.wallet.or(self.wallet),
+            password,
+            files: [self.files, other.files].concat(),
+            ignored: [self.ignored, other.ignored].concat(),
+        }
+    }
+
+    /// The request timeout in seconds
+    pub fn timeout(&self) -> u64 {
+        self.api.timeout.unwrap_or(DEFAULT_TIMEOUT)
+    }
+}
+
+impl Password {
+    pub fn is_set(&self) -> bool {
+        self.file.is_some() || self.command.is_some()
+    }
+
+    /// The configured password source, if any
+    pub fn source(&self) -> Option<PasswordSource> {
+        match (&self.file, &self.command) {
+            (Some(file), _) => Some(PasswordSource::File(file.clone())),
+            (_, Some(command)) => Some(PasswordSource::Command(command.clone())),
+            _ => None,
+        }
+    }
+}
+
+/// The nearest project config file in the given directory or its parents
+fn project_file(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_config() {
+        let global: Config = toml::from_str(
+            r#"
+            format = "json"
+            wallet = "hot"
+
+            [api]
+            mainnet = "https://api.example.com/v1"
+            timeout = 30
+
+            [password]
+            file = "/run/secrets/wallet"
+            "#,
+        )
+        .expect("global config");
+        let project: Config = toml::from_str(
+            r#"
+            wallet = "project"
+
+            [api]
+            testnet = "https://testnet.example.com/v1"
+
+            [password]
+            command = "echo secret"
+            "#,
+        )
+        .expect("project config");
+        let config = global.merge(project);
+        assert_eq!(Some("json".to_string()), config.format);
+        assert_eq!(Some("project".to_string()), config.wallet);
+        assert_eq!(
+            Some("https://api.example.com/v1".to_string()),
+            config.api.mainnet
+        );
+        assert_eq!(
+            Some("https://testnet.example.com/v1".to_string()),
+            config.api.testnet
+        );
+        assert_eq!(30, config.timeout());
+        assert_eq!(None, config.password.file);
+        assert_eq!(
+            Some(PasswordSource::Command("echo secret".to_string())),
+            config.password.source()
+        );
+
+        assert!(toml::from_str::<Config>("colour = \"red\"").is_err());
+        assert_eq!(DEFAULT_TIMEOUT, Config::default().timeout());
+    }
+
+    #[test]
+    fn project_settings() {
+        let mut project: Config = toml::from_str(
+            r#"
+            wallet = "project"
+
+            [api]
+            staking = "https://staking.example.com"
+
+            [password]
+            command = "echo secret"
+            "#,
+        )
+        .expect("project config");
+        project.files.push(PathBuf::from(PROJECT_CONFIG_FILE));
+        let config = Config::default().merge(project.project_settings());
+        assert_eq!(Some("project".to_string()), config.wallet);
+        assert_eq!(Api::default(), config.api);
+        assert_eq!(None, config.password.source());
+        assert_eq!(
+            vec![
+                (
+                    "api.staking".to_string(),
+                    PathBuf::from(PROJECT_CONFIG_FILE)
+                ),
+                (
+                    "password.command".to_string(),
+                    PathBuf::from(PROJECT_CONFIG_FILE)
+                ),
+            ],
+            config.ignored
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use crate::{
+    keypair::PublicKey,
+    keyring,
+    result::{anyhow, bail, Result},
+};
+use std::{
+    collections::BTreeMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable to override the address book file with
+pub const CONTACTS_ENV: &str = "HELIUM_WALLET_CONTACTS";
+
+/// A local address book mapping aliases to addresses. The address book is
+/// stored as a json object of aliases and b58 addresses.
+#[derive(Debug, Default)]
+pub struct AddressBook {
+    path: PathBuf,
+    contacts: BTreeMap<String, PublicKey>,
+}
+
+impl AddressBook {
+    /// Opens the address book of the current user. This is the file given
+    /// by `HELIUM_WALLET_CONTACTS` or `helium-wallet/contacts.json` in the
+    /// XDG config directory. A missing file is an empty address book.
+    pub fn open() -> Result<Self> {
+        let path = match env::var_os(CONTACTS_ENV) {
+            Some(path) => PathBuf::from(path),
+            None => keyring::config_dir()?.join("contacts.json"),
+        };
+        Self::load(&path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let entries: BTreeMap<String, String> = match fs::read(path) {
+            Ok(data) => serde_json::from_slice(&data)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(err.into()),
+        };
+        let mut contacts = BTreeMap::new();
+        for (alias, address) in entries {
+            let address = address
+                .parse()
+                .map_err(|err| anyhow!("Invalid address for contact @{}: {}", alias, err))?;
+            contacts.insert(alias, address);
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            contacts,
+        })
+    }
+
+    pub fn save(&self) -> Result {
+        let entries: BTreeMap<&String, String> = self
+            .contacts
+            .iter()
+            .map(|(alias, address)| (alias, address.to_string()))
+            .collect();
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&entries)? + "\n")?;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn add(&mut self, alias: &str, address: PublicKey) -> Result {
+        check_alias(alias)?;
+        if self.contacts.contains_key(alias) {
+            bail!("Contact @{} already exists", alias);
+        }
+        self.contacts.insert(alias.to_string(), address);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, alias: &str) -> Result<PublicKey> {
+        self.contacts
+            .remove(alias)
+            .ok_or_else(|| anyhow!("Unknown contact @{}", alias))
+    }
+
+    pub fn get(&self, alias: &str) -> Result<&PublicKey> {
+        self.contacts
+            .get(alias)
+            .ok_or_else(|| anyhow!("Unknown contact @{}", alias))
+    }
+
+    /// All contacts in alias order
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &PublicKey)> {
+        self.contacts.iter()
+    }
+
+    /// The alias of the given address, if it is a contact
+    pub fn alias(&self, address: &PublicKey) -> Option<&str> {
+        self.contacts
+            .iter()
+            .find(|(_, contact)| *contact == address)
+            .map(|(alias, _)| alias.as_str())
+    }
+
+    /// Returns the contact whose address is one character away, by a single
+    /// insertion, deletion or substitution, from the given string. Such a
+    /// string fails the address checksum and is most likely a mistyped copy
+    /// of the contact's address.
+    pub fn near_miss(&self, address: &str) -> Option<(&str, &PublicKey)> {
+        self.contacts
+            .iter()
+            .find(|(_, contact)| one_edit_apart(&contact.to_string(), address))
+            .map(|(alias, contact)| (alias.as_str(), contact))
+    }
+
+    /// Formats an address with its alias, if it is a contact
+    pub fn display(&self, address: &PublicKey) -> String {
+        match self.alias(address) {
+            Some(alias) => format!("{} (@{})", address, alias),
+            None => address.to_string(),
+        }
+    }
+}
+
+/// Parses an address, looking up addresses of the form `@alias` in the
+/// address book. An invalid address that is one character away from a
+/// contact is reported with that contact's alias.
+pub fn parse_address(s: &str) -> Result<PublicKey> {
+    if let Some(alias) = s.strip_prefix('@') {
+        return Ok(AddressBook::open()?.get(alias)?.clone());
+    }
+    match s.parse() {
+        Ok(address) => Ok(address),
+        Err(err) => {
+            if let Ok(contacts) = AddressBook::open() {
+                if let Some((alias, address)) = contacts.near_miss(s) {
+                    bail!(
+                        "Invalid address {}, did you mean @{} ({})?",
+                        s,
+                        alias,
+                        address
+                    );
+                }
+            }
+            Err(err.into())
+        }
+    }
+}
+
+fn check_alias(alias: &str) -> Result {
+    let valid = !alias.is_empty()
+        && alias
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    if !valid {
+        bail!(
+            "Invalid alias \"{}\". Use letters, digits, '-', '_' and '.'",
+            alias
+        );
+    }
+    Ok(())
+}
+
+/// Whether the two strings differ by exactly one inserted, deleted or
+/// substituted character
+fn one_edit_apart(a: &str, b: &str) -> bool {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let (shorter, longer) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if longer.len() - shorter.len() > 1 {
+        return false;
+    }
+    let prefix = shorter
+        .iter()
+        .zip(&longer)
+        .take_while(|(x, y)| x == y)
+        .count();
+    if shorter.len() == longer.len() {
+        prefix < shorter.len() && shorter[prefix + 1..] == longer[prefix + 1..]
+    } else {
+        shorter[prefix..] == longer[prefix + 1..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_edit() {
+        assert!(one_edit_apart("13abc", "13abd"));
+        assert!(one_edit_apart("13abc", "13ac"));
+        assert!(one_edit_apart("13ac", "13abc"));
+        assert!(one_edit_apart("13abc", "13abcd"));
+        assert!(!one_edit_apart("13abc", "13abc"));
+        assert!(!one_edit_apart("13abc", "13bad"));
+        assert!(!one_edit_apart("13abc", "13a"));
+    }
+}
@@ -1,25 +1,41 @@
 use crate::{
     pwhash::PwHash,
-    result::{bail, Result},
+    result::{anyhow, bail, Result},
+};
+use aes_gcm::{
+    aead::{generic_array::GenericArray, NewAead},
+    AeadInPlace, Aes256Gcm,
 };
 use byteorder::{ReadBytesExt, WriteBytesExt};
 use hmac::{Hmac, Mac, NewMac};
 use sha2::Sha256;
 use shamirsecretsharing::hazmat::{combine_keyshares, create_keyshares};
 use sodiumoxide::randombytes;
-use std::{fmt, io};
+use std::{
+    fmt, fs,
+    io::{self, Read},
+    path::Path,
+};
+use zeroize::Zeroizing;
 
 #[derive(Clone)]
 pub enum Format {
     Basic(Basic),
     Sharded(Sharded),
+    Slots(Slots),
 }
 
 impl Format {
-    pub fn derive_key(&mut self, password: &[u8], key: &mut [u8]) -> Result {
+    pub fn derive_key(
+        &mut self,
+        password: &[u8],
+        keyfile: Option<&Keyfile>,
+        key: &mut [u8],
+    ) -> Result {
         match self {
-            Format::Basic(derive) => derive.derive_key(password, key),
-            Format::Sharded(derive) => derive.derive_key(password, key),
+            Format::Basic(derive) => derive.derive_key(password, keyfile, key),
+            Format::Sharded(derive) => derive.derive_key(password, keyfile, key),
+            Format::Slots(derive) => derive.derive_key(password, keyfile, key),
         }
     }
 
@@ -27,6 +43,7 @@ impl Format {
         match self {
             Format::Basic(derive) => derive.mut_pwhash(),
             Format::Sharded(derive) => derive.mut_pwhash(),
+            Format::Slots(derive) => derive.mut_pwhash(),
         }
     }
 
@@ -34,6 +51,7 @@ impl Format {
         match self {
             Format::Basic(derive) => derive.pwhash(),
             Format::Sharded(derive) => derive.pwhash(),
+            Format::Slots(derive) => derive.pwhash(),
         }
     }
 
@@ -41,6 +59,7 @@ impl Format {
         match self {
             Format::Basic(derive) => derive.read(reader),
             Format::Sharded(derive) => derive.read(reader),
+            Format::Slots(derive) => derive.read(reader),
         }
     }
 
@@ -48,6 +67,7 @@ impl Format {
         match self {
             Format::Basic(derive) => derive.write(writer),
             Format::Sharded(derive) => derive.write(writer),
+            Format::Slots(derive) => derive.write(writer),
         }
     }
 
@@ -67,6 +87,29 @@ impl Format {
     pub fn sharded_default(pwhash: PwHash) -> Self {
         Self::sharded(5, 3, pwhash)
     }
+
+    pub fn slots(pwhash: PwHash) -> Self {
+        Format::Slots(Slots {
+            pwhash,
+            key_slots: Vec::new(),
+        })
+    }
+
+    /// Returns a format with the same settings as this one but a newly
+    /// salted password hash. Sharded formats are returned without key
+    /// shares so a fresh set is generated when a wallet is encrypted with
+    /// it.
+    pub fn renew(&self) -> Self {
+        match self {
+            Format::Basic(derive) => Self::basic(derive.pwhash.with_new_salt()),
+            Format::Sharded(derive) => Self::sharded(
+                derive.key_share_count,
+                derive.recovery_threshold,
+                derive.pwhash.with_new_salt(),
+            ),
+            Format::Slots(derive) => Self::slots(derive.pwhash.with_new_salt()),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -75,8 +118,13 @@ pub struct Basic {
 }
 
 impl Basic {
-    pub fn derive_key(&mut self, password: &[u8], key: &mut [u8]) -> Result {
-        self.pwhash.pwhash(password, key)
+    pub fn derive_key(
+        &mut self,
+        password: &[u8],
+        keyfile: Option<&Keyfile>,
+        key: &mut [u8],
+    ) -> Result {
+        stretch_key(&self.pwhash, password, keyfile, key)
     }
 
     pub fn mut_pwhash(&mut self) -> &mut PwHash {
@@ -116,6 +164,12 @@ impl KeyShare {
         self.0.to_vec()
     }
 
+    /// The index (x coordinate) of this key share. Distinct shards of a
+    /// wallet have distinct indices.
+    pub fn index(&self) -> u8 {
+        self.0[0]
+    }
+
     pub fn from_slice(slice: &[u8]) -> KeyShare {
         let mut share = [0u8; 33];
         share.copy_from_slice(slice);
@@ -132,43 +186,53 @@ pub struct Sharded {
 }
 
 impl Sharded {
-    pub fn derive_key(&mut self, password: &[u8], key: &mut [u8]) -> Result {
-        self.pwhash.pwhash(password, key)?;
-
-        let mut sss_key: [u8; 32] = [0; 32];
+    pub fn derive_key(
+        &mut self,
+        password: &[u8],
+        keyfile: Option<&Keyfile>,
+        key: &mut [u8],
+    ) -> Result {
+        stretch_key(&self.pwhash, password, keyfile, key)?;
 
         if self.key_shares.is_empty() {
             // Generate the keyhares when we have none
-            randombytes::randombytes_into(&mut sss_key);
+            let mut sss_key = Zeroizing::new([0u8; 32]);
+            randombytes::randombytes_into(&mut *sss_key);
             let key_share_vecs =
-                create_keyshares(&sss_key, self.key_share_count, self.recovery_threshold)?;
+                create_keyshares(&*sss_key, self.key_share_count, self.recovery_threshold)?;
             let mut key_shares = vec![];
             for share_vec in key_share_vecs {
                 key_shares.push(KeyShare::from_slice(&share_vec));
             }
             self.key_shares = key_shares;
+            mix_key(&*sss_key, key)
         } else if self.key_shares.len() < self.recovery_threshold as usize {
             // Otherwise validate that we can reconstruct the key
             bail!("not enouth keyshares to recover key");
         } else {
-            // Reconstruct shared key
-            let key_share_vecs: Vec<Vec<u8>> =
-                self.key_shares.iter().map(|sh| sh.to_vec()).collect();
-            match combine_keyshares(&key_share_vecs) {
-                Ok(k) => sss_key.copy_from_slice(&k),
-                Err(_) => bail!("Failed to combine keyshares"),
-            }
+            let key_shares: Vec<&KeyShare> = self.key_shares.iter().collect();
+            Self::combine_key(&key_shares, key)
         }
+    }
 
+    /// Reconstructs the shared key from the given key shares and mixes it
+    /// into the given, already stretched, password key to form the
+    /// encryption key.
+    pub fn combine_key(key_shares: &[&KeyShare], key: &mut [u8]) -> Result {
+        let key_share_vecs: Vec<Vec<u8>> = key_shares.iter().map(|sh| sh.to_vec()).collect();
+        let sss_key = match combine_keyshares(&key_share_vecs) {
+            Ok(k) => Zeroizing::new(k),
+            Err(_) => bail!("Failed to combine keyshares"),
+        };
         // Now go derive the encryption key from the sharded key
         // source and the stretched key
-        let mut hmac = match Hmac::<Sha256>::new_from_slice(&sss_key) {
-            Err(_) => bail!("Failed to initialize hmac"),
-            Ok(m) => m,
-        };
-        hmac.update(key);
-        key.copy_from_slice(&hmac.finalize().into_bytes());
-        Ok(())
+        mix_key(&sss_key, key)
+    }
+
+    /// Returns all sets of key share indices that have exactly the number
+    /// of key shares needed to recover the key.
+    pub fn key_share_subsets(&self) -> impl Iterator<Item = Vec<usize>> {
+        combinations(self.key_shares.len(), self.recovery_threshold as usize)
     }
 
     pub fn mut_pwhash(&mut self) -> &mut PwHash {
@@ -220,3 +284,291 @@ impl Sharded {
         Ok(())
     }
 }
+
+/// A key slot holds the data key of a key slot wallet encrypted with a key
+/// derived from the password of that slot.
+#[derive(Clone)]
+pub struct KeySlot {
+    pub pwhash: PwHash,
+    iv: [u8; 12],
+    tag: [u8; 16],
+    sealed_key: [u8; 32],
+}
+
+impl fmt::Debug for KeySlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeySlot")
+            .field("pwhash", &self.pwhash)
+            .finish_non_exhaustive()
+    }
+}
+
+impl KeySlot {
+    /// Seals the given data key in a new slot that is unlocked by the given
+    /// password and keyfile
+    pub fn seal(
+        data_key: &[u8; 32],
+        password: &[u8],
+        keyfile: Option<&Keyfile>,
+        pwhash: PwHash,
+    ) -> Result<Self> {
+        let mut slot_key = Zeroizing::new([0u8; 32]);
+        stretch_key(&pwhash, password, keyfile, &mut *slot_key)?;
+        let mut iv = [0u8; 12];
+        randombytes::randombytes_into(&mut iv);
+        let aead = Aes256Gcm::new(GenericArray::from_slice(&*slot_key));
+        let associated_data = Self::associated_data(&pwhash)?;
+        let mut sealed_key = *data_key;
+        let tag = aead
+            .encrypt_in_place_detached(iv.as_ref().into(), &associated_data, &mut sealed_key)
+            .map_err(|_| anyhow!("Failed to seal key slot"))?;
+        Ok(Self {
+            pwhash,
+            iv,
+            tag: tag.into(),
+            sealed_key,
+        })
+    }
+
+    /// Returns the data key sealed in this slot if the password and keyfile
+    /// unlock it
+    pub fn unseal(
+        &self,
+        password: &[u8],
+        keyfile: Option<&Keyfile>,
+    ) -> Result<Zeroizing<[u8; 32]>> {
+        let mut slot_key = Zeroizing::new([0u8; 32]);
+        stretch_key(&self.pwhash, password, keyfile, &mut *slot_key)?;
+        let aead = Aes256Gcm::new(GenericArray::from_slice(&*slot_key));
+        let associated_data = Self::associated_data(&self.pwhash)?;
+        let mut data_key = Zeroizing::new(self.sealed_key);
+        aead.decrypt_in_place_detached(
+            self.iv.as_ref().into(),
+            &associated_data,
+            &mut *data_key,
+            self.tag.as_ref().into(),
+        )
+        .map_err(|_| anyhow!("Failed to unseal key slot"))?;
+        Ok(data_key)
+    }
+
+    /// The password hash kind and parameters of a slot are authenticated
+    /// with its sealed key
+    fn associated_data(pwhash: &PwHash) -> Result<Vec<u8>> {
+        let mut associated_data = vec![];
+        pwhash.write_kind(&mut associated_data)?;
+        pwhash.write(&mut associated_data)?;
+        Ok(associated_data)
+    }
+
+    fn read(reader: &mut dyn io::Read) -> Result<Self> {
+        let mut pwhash = PwHash::read_kind(reader)?;
+        pwhash.read(reader)?;
+        let mut slot = Self {
+            pwhash,
+            iv: [0u8; 12],
+            tag: [0u8; 16],
+            sealed_key: [0u8; 32],
+        };
+        reader.read_exact(&mut slot.iv)?;
+        reader.read_exact(&mut slot.tag)?;
+        reader.read_exact(&mut slot.sealed_key)?;
+        Ok(slot)
+    }
+
+    fn write(&self, writer: &mut dyn io::Write) -> Result {
+        self.pwhash.write_kind(writer)?;
+        self.pwhash.write(writer)?;
+        writer.write_all(&self.iv)?;
+        writer.write_all(&self.tag)?;
+        writer.write_all(&self.sealed_key)?;
+        Ok(())
+    }
+}
+
+/// A wallet format with one or more key slots. The wallet is encrypted with
+/// a random data key which every slot seals under a key derived from its own
+/// password, so passwords can be added and removed without re-encrypting
+/// the wallet.
+///
+/// Unlocking tries the slots in turn, each with a full password hash, so a
+/// wallet is limited to `MAX_KEY_SLOTS` slots.
+#[derive(Clone, Debug)]
+pub struct Slots {
+    /// The password hash of the first slot. Used to create that slot when
+    /// a new wallet is encrypted.
+    pub pwhash: PwHash,
+    pub key_slots: Vec<KeySlot>,
+}
+
+/// The most key slots a wallet can have
+pub const MAX_KEY_SLOTS: usize = 8;
+
+impl Slots {
+    pub fn derive_key(
+        &mut self,
+        password: &[u8],
+        keyfile: Option<&Keyfile>,
+        key: &mut [u8],
+    ) -> Result {
+        if self.key_slots.is_empty() {
+            // Generate the data key and the first slot when we have none
+            let mut data_key = Zeroizing::new([0u8; 32]);
+            randombytes::randombytes_into(&mut *data_key);
+            let slot = KeySlot::seal(&data_key, password, keyfile, self.pwhash)?;
+            self.key_slots.push(slot);
+            key.copy_from_slice(&*data_key);
+        } else {
+            let (_, data_key) = self.unseal(password, keyfile)?;
+            key.copy_from_slice(&*data_key);
+        }
+        Ok(())
+    }
+
+    /// Tries each key slot in turn and returns the index of the first slot
+    /// the password and keyfile unlock together with its data key
+    pub fn unseal(
+        &self,
+        password: &[u8],
+        keyfile: Option<&Keyfile>,
+    ) -> Result<(usize, Zeroizing<[u8; 32]>)> {
+        for (index, slot) in self.key_slots.iter().enumerate() {
+            if let Ok(data_key) = slot.unseal(password, keyfile) {
+                return Ok((index, data_key));
+            }
+        }
+        bail!("Failed to decrypt wallet")
+    }
+
+    pub fn mut_pwhash(&mut self) -> &mut PwHash {
+        &mut self.pwhash
+    }
+
+    pub fn pwhash(&self) -> &PwHash {
+        &self.pwhash
+    }
+
+    pub fn read(&mut self, reader: &mut dyn io::Read) -> Result {
+        let slot_count = reader.read_u8()?;
+        if slot_count == 0 || slot_count as usize > MAX_KEY_SLOTS {
+            bail!("Invalid number of key slots {}", slot_count);
+        }
+        for _ in 0..slot_count {
+            self.key_slots.push(KeySlot::read(reader)?);
+        }
+        self.pwhash = self.key_slots[0].pwhash;
+        Ok(())
+    }
+
+    pub fn write(&self, writer: &mut dyn io::Write) -> Result {
+        if self.key_slots.is_empty() || self.key_slots.len() > MAX_KEY_SLOTS {
+            bail!("Invalid number of key slots {}", self.key_slots.len());
+        }
+        writer.write_u8(self.key_slots.len() as u8)?;
+        for slot in &self.key_slots {
+            slot.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// The contents of a keyfile that is needed in addition to the password to
+/// decrypt a wallet. Any file can be used as a keyfile.
+pub struct Keyfile(Zeroizing<Vec<u8>>);
+
+impl Keyfile {
+    pub fn new(contents: Vec<u8>) -> Result<Self> {
+        if contents.is_empty() {
+            bail!("Keyfile is empty");
+        }
+        Ok(Self(Zeroizing::new(contents)))
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        // Sized up front so that reading does not reallocate the buffer and
+        // leave unzeroized copies of the contents behind
+        let mut file = fs::File::open(path)?;
+        let len = file.metadata()?.len() as usize;
+        let mut contents = Zeroizing::new(Vec::with_capacity(len + 1));
+        file.read_to_end(&mut contents)?;
+        if contents.is_empty() {
+            bail!("Keyfile is empty");
+        }
+        Ok(Self(contents))
+    }
+}
+
+/// Stretches the password into the given key using the password hash and,
+/// if given, mixes in the keyfile
+pub fn stretch_key(
+    pwhash: &PwHash,
+    password: &[u8],
+    keyfile: Option<&Keyfile>,
+    key: &mut [u8],
+) -> Result {
+    pwhash.pwhash(password, key)?;
+    match keyfile {
+        Some(keyfile) => mix_key(&keyfile.0, key),
+        None => Ok(()),
+    }
+}
+
+/// Mixes the given secret into the key using a sha256 HMAC keyed with the
+/// secret
+fn mix_key(secret: &[u8], key: &mut [u8]) -> Result {
+    let mut hmac = match Hmac::<Sha256>::new_from_slice(secret) {
+        Err(_) => bail!("Failed to initialize hmac"),
+        Ok(m) => m,
+    };
+    hmac.update(key);
+    key.copy_from_slice(&hmac.finalize().into_bytes());
+    Ok(())
+}
+
+/// Iterates over all k sized subsets of the indices 0..n in lexicographic
+/// order.
+fn combinations(n: usize, k: usize) -> impl Iterator<Item = Vec<usize>> {
+    let first = if k <= n {
+        Some((0..k).collect::<Vec<usize>>())
+    } else {
+        None
+    };
+    std::iter::successors(first, move |prev| {
+        let mut next = prev.clone();
+        // Find the rightmost index that can still be moved right and reset
+        // all indices after it
+        for i in (0..k).rev() {
+            if next[i] < n - k + i {
+                next[i] += 1;
+                for j in i + 1..k {
+                    next[j] = next[j - 1] + 1;
+                }
+                return Some(next);
+            }
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combinations_lexicographic() {
+        let subsets: Vec<Vec<usize>> = combinations(4, 2).collect();
+        assert_eq!(
+            vec![
+                vec![0, 1],
+                vec![0, 2],
+                vec![0, 3],
+                vec![1, 2],
+                vec![1, 3],
+                vec![2, 3]
+            ],
+            subsets
+        );
+        assert_eq!(1, combinations(3, 3).count());
+        assert_eq!(0, combinations(2, 3).count());
+    }
+}
@@ -0,0 +1,195 @@
+use crate::result::{anyhow, bail, Error, Result};
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha512;
+use std::{fmt, str::FromStr};
+use zeroize::Zeroize;
+
+/// Offset for hardened child indices. SLIP-0010 only supports hardened
+/// derivation for ed25519 keys.
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// The SLIP-0044 coin type registered for Helium
+pub const HELIUM_COIN_TYPE: u32 = 904;
+
+const ED25519_SEED_KEY: &[u8] = b"ed25519 seed";
+
+/// A BIP32 style derivation path like `m/44'/904'/0'/0'/0'`. Since ed25519
+/// only supports hardened derivation every index in the path must be
+/// hardened.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// Returns the BIP44 path for the given account number, i.e.
+    /// `m/44'/904'/account'/0'/0'`
+    pub fn account(account: u32) -> Result<Self> {
+        if account >= HARDENED_OFFSET {
+            bail!("Account number must be less than {}", HARDENED_OFFSET);
+        }
+        Ok(Self(vec![
+            44 + HARDENED_OFFSET,
+            HELIUM_COIN_TYPE + HARDENED_OFFSET,
+            account + HARDENED_OFFSET,
+            HARDENED_OFFSET,
+            HARDENED_OFFSET,
+        ]))
+    }
+
+    pub fn indices(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.trim().split('/');
+        if parts.next() != Some("m") {
+            bail!("Derivation path must start with \"m\"");
+        }
+        let mut indices = vec![];
+        for part in parts {
+            let index = match part.strip_suffix(|c| c == '\'' || c == 'h' || c == 'H') {
+                Some(index) => index,
+                None => bail!("Only hardened derivation is supported, use {}'", part),
+            };
+            let index: u32 = index
+                .parse()
+                .map_err(|_| anyhow!("Invalid derivation path index {}", part))?;
+            if index >= HARDENED_OFFSET {
+                bail!("Derivation path index {} is too large", part);
+            }
+            indices.push(index + HARDENED_OFFSET);
+        }
+        Ok(Self(indices))
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("m")?;
+        for index in &self.0 {
+            write!(f, "/{}'", index - HARDENED_OFFSET)?;
+        }
+        Ok(())
+    }
+}
+
+/// A SLIP-0010 extended ed25519 private key. The key and chain code are
+/// zeroized on drop.
+pub struct ExtendedKey {
+    pub key: [u8; 32],
+    pub chain_code: [u8; 32],
+}
+
+impl Drop for ExtendedKey {
+    fn drop(&mut self) {
+        self.key.zeroize();
+        self.chain_code.zeroize();
+    }
+}
+
+impl ExtendedKey {
+    /// Derives the master key from a seed, for example one produced by
+    /// `mnemonic::mnemonic_to_seed`
+    pub fn master(seed: &[u8]) -> Result<Self> {
+        Self::from_hmac(ED25519_SEED_KEY, &[seed])
+    }
+
+    /// Derives the key at the given path from a seed
+    pub fn derive(seed: &[u8], path: &DerivationPath) -> Result<Self> {
+        let mut key = Self::master(seed)?;
+        for index in path.indices() {
+            key = key.derive_child(*index)?;
+        }
+        Ok(key)
+    }
+
+    /// Derives the hardened child key with the given index
+    pub fn derive_child(&self, index: u32) -> Result<Self> {
+        if index < HARDENED_OFFSET {
+            bail!("Only hardened derivation is supported for ed25519 keys");
+        }
+        Self::from_hmac(&self.chain_code, &[&[0u8], &self.key, &index.to_be_bytes()])
+    }
+
+    fn from_hmac(key: &[u8], data: &[&[u8]]) -> Result<Self> {
+        let mut hmac = match Hmac::<Sha512>::new_from_slice(key) {
+            Err(_) => bail!("Failed to initialize hmac"),
+            Ok(m) => m,
+        };
+        for data in data {
+            hmac.update(data);
+        }
+        let mut result = hmac.finalize().into_bytes();
+        let mut extended = Self {
+            key: [0u8; 32],
+            chain_code: [0u8; 32],
+        };
+        extended.key.copy_from_slice(&result[..32]);
+        extended.chain_code.copy_from_slice(&result[32..]);
+        result[..].zeroize();
+        Ok(extended)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vector 1 for ed25519 from the SLIP-0010 specification
+    const SEED: &str = "000102030405060708090a0b0c0d0e0f";
+    const VECTORS: &[(&str, &str, &str)] = &[
+        (
+            "m",
+            "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb",
+            "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+        ),
+        (
+            "m/0'",
+            "8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69",
+            "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+        ),
+        (
+            "m/0'/1'",
+            "a320425f77d1b5c2505a6b1b27382b37368ee640e3557c315416801243552f14",
+            "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2",
+        ),
+        (
+            "m/0'/1'/2'",
+            "2e69929e00b5ab250f49c3fb1c12f252de4fed2c1db88387094a0f8c4c9ccd6c",
+            "92a5b23c0b8a99e37d07df3fb9966917f5d06e02ddbd909c7e184371463e9fc9",
+        ),
+        (
+            "m/0'/1'/2'/2'",
+            "8f6d87f93d750e0efccda017d662a1b31a266e4a6f5993b15f5c1f07f74dd5cc",
+            "30d1dc7e5fc04c31219ab25a27ae00b50f6fd66622f6e9c913253d6511d1e662",
+        ),
+        (
+            "m/0'/1'/2'/2'/1000000000'",
+            "68789923a0cac2cd5a29172a475fe9e0fb14cd6adb5ad98a3fa70333e7afa230",
+            "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+        ),
+    ];
+
+    #[test]
+    fn slip10_ed25519_vector_1() {
+        let seed = hex::decode(SEED).expect("seed");
+        for (path, chain_code, key) in VECTORS {
+            let path: DerivationPath = path.parse().expect("derivation path");
+            let derived = ExtendedKey::derive(&seed, &path).expect("derived key");
+            assert_eq!(*chain_code, hex::encode(derived.chain_code), "{}", path);
+            assert_eq!(*key, hex::encode(derived.key), "{}", path);
+        }
+    }
+
+    #[test]
+    fn parse_derivation_path() {
+        let path: DerivationPath = "m/44'/904'/3h/0H/0'".parse().expect("derivation path");
+        assert_eq!(DerivationPath::account(3).expect("account path"), path);
+        assert_eq!("m/44'/904'/3'/0'/0'", path.to_string());
+        assert!("m/44'/904/0'".parse::<DerivationPath>().is_err());
+        assert!("44'/904'".parse::<DerivationPath>().is_err());
+    }
+}
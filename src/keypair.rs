@@ -1,15 +1,28 @@
-use crate::{result::Result, traits::ReadWrite};
+use crate::{
+    result::{anyhow, bail, Result},
+    traits::ReadWrite,
+};
 use byteorder::ReadBytesExt;
-use std::{convert::TryFrom, io};
+use std::{convert::TryFrom, fmt, io};
+use zeroize::Zeroizing;
 
 pub use helium_crypto::{
     ecc_compact, ed25519, KeyTag, KeyType, Network, PublicKey, Sign, Verify, KEYTYPE_ED25519_STR,
     NETTYPE_MAIN_STR, PUBLIC_KEY_LENGTH,
 };
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq)]
 pub struct Keypair(helium_crypto::Keypair);
 
+impl fmt::Debug for Keypair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Only the public key, the private key is redacted
+        f.debug_struct("Keypair")
+            .field("public_key", self.public_key())
+            .finish_non_exhaustive()
+    }
+}
+
 static START: std::sync::Once = std::sync::Once::new();
 
 fn init() {
@@ -41,6 +54,237 @@ impl Keypair {
     pub fn sign(&self, msg: &[u8]) -> Result<Vec<u8>> {
         Ok(self.0.sign(msg)?)
     }
+
+    pub fn key_type(&self) -> KeyType {
+        match &self.0 {
+            helium_crypto::Keypair::Ed25519(_) => KeyType::Ed25519,
+            helium_crypto::Keypair::EccCompact(_) => KeyType::EccCompact,
+        }
+    }
+
+    /// Returns the key tag, private key and public key bytes of this
+    /// keypair. This is the same layout as a validator or miner `swarm_key`.
+    pub fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        match &self.0 {
+            helium_crypto::Keypair::Ed25519(key) => Zeroizing::new(key.to_bytes().to_vec()),
+            helium_crypto::Keypair::EccCompact(key) => Zeroizing::new(key.to_bytes().to_vec()),
+        }
+    }
+
+    /// The number of bytes written by `ReadWrite::write` for this keypair
+    pub(crate) fn encoded_len(&self) -> usize {
+        match &self.0 {
+            helium_crypto::Keypair::Ed25519(_) => ed25519::KEYPAIR_LENGTH + PUBLIC_KEY_LENGTH,
+            helium_crypto::Keypair::EccCompact(_) => {
+                ecc_compact::KEYPAIR_LENGTH + PUBLIC_KEY_LENGTH
+            }
+        }
+    }
+
+    /// Returns the raw private key bytes of this keypair
+    pub(crate) fn secret_bytes(&self) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(self.to_bytes()[1..SECRET_KEY_LENGTH + 1].to_vec())
+    }
+
+    /// Encodes the private key of this keypair as an unencrypted PKCS#8
+    /// `PrivateKeyInfo` in DER format
+    pub fn to_pkcs8_der(&self) -> Result<Vec<u8>> {
+        let secret = self.secret_bytes();
+        let (algorithm, private_key) = match self.key_type() {
+            // RFC 8410: the private key is the ed25519 seed as an octet string
+            KeyType::Ed25519 => (der(0x30, &der(0x06, OID_ED25519)), der(0x04, &secret)),
+            // RFC 5915: an ECPrivateKey with the uncompressed public key
+            KeyType::EccCompact => {
+                let (x, y) = p256_public_point(&secret)?;
+                let mut point = vec![0x00, 0x04];
+                point.extend_from_slice(&x);
+                point.extend_from_slice(&y);
+                let ec_private_key = der(
+                    0x30,
+                    &[
+                        der(0x02, &[0x01]),
+                        der(0x04, &secret),
+                        der(0xa1, &der(0x03, &point)),
+                    ]
+                    .concat(),
+                );
+                (
+                    der(
+                        0x30,
+                        &[der(0x06, OID_EC_PUBLIC_KEY), der(0x06, OID_P256)].concat(),
+                    ),
+                    ec_private_key,
+                )
+            }
+        };
+        Ok(der(
+            0x30,
+            &[der(0x02, &[0x00]), algorithm, der(0x04, &private_key)].concat(),
+        ))
+    }
+
+    /// Encodes this keypair as a JSON Web Key (RFC 8037 for ed25519 and
+    /// RFC 7518 for ecc_compact keys)
+    pub fn to_jwk(&self) -> Result<serde_json::Value> {
+        let b64 = |bytes: &[u8]| base64::encode_config(bytes, base64::URL_SAFE_NO_PAD);
+        let secret = self.secret_bytes();
+        match self.key_type() {
+            KeyType::Ed25519 => Ok(serde_json::json!({
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "d": b64(&secret),
+                "x": b64(&self.public_key().to_bytes()[1..]),
+            })),
+            KeyType::EccCompact => {
+                let (x, y) = p256_public_point(&secret)?;
+                Ok(serde_json::json!({
+                    "kty": "EC",
+                    "crv": "P-256",
+                    "d": b64(&secret),
+                    "x": b64(&x),
+                    "y": b64(&y),
+                }))
+            }
+        }
+    }
+
+    /// Constructs a keypair from tagged key bytes as written by `to_bytes`,
+    /// `ReadWrite::write` or a `swarm_key` file. Only the key tag and the
+    /// private key are used; the public key is derived from the private key.
+    /// An embedded ed25519 public key must match the derived one.
+    pub fn from_tagged_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < SECRET_KEY_LENGTH + 1 {
+            bail!("Key data too short");
+        }
+        let network = match bytes[0] & 0xF0 {
+            0x00 => Network::MainNet,
+            0x10 => Network::TestNet,
+            other => bail!("Invalid network tag {:#04x}", other),
+        };
+        let tag = KeyTag {
+            network,
+            key_type: KeyType::try_from(bytes[0])?,
+        };
+        let keypair = Self::generate_from_entropy(tag, &bytes[1..SECRET_KEY_LENGTH + 1])?;
+        if let KeyType::Ed25519 = tag.key_type {
+            let embedded = bytes.get(SECRET_KEY_LENGTH + 1..2 * SECRET_KEY_LENGTH + 1);
+            let derived = &keypair.public_key().to_bytes()[1..];
+            if matches!(embedded, Some(embedded) if embedded != derived) {
+                bail!("Public key in key data does not match its private key");
+            }
+        }
+        Ok(keypair)
+    }
+
+    /// Constructs a keypair from an unencrypted PKCS#8 `PrivateKeyInfo` in
+    /// DER format holding an ed25519 or P-256 private key. Since PKCS#8 has
+    /// no notion of a Helium network the network has to be given.
+    pub fn from_pkcs8_der(der: &[u8], network: Network) -> Result<Self> {
+        let (private_key_info, _) = der_read(0x30, der)?;
+        let (_version, rest) = der_read(0x02, private_key_info)?;
+        let (algorithm, rest) = der_read(0x30, rest)?;
+        let (private_key, _) = der_read(0x04, rest)?;
+        let (oid, parameters) = der_read(0x06, algorithm)?;
+        match oid {
+            OID_ED25519 => {
+                let (secret, _) = der_read(0x04, private_key)?;
+                Self::from_secret(network, KeyType::Ed25519, secret)
+            }
+            OID_EC_PUBLIC_KEY => {
+                if der_read(0x06, parameters)?.0 != OID_P256 {
+                    bail!("Only P-256 EC private keys are supported");
+                }
+                let (ec_private_key, _) = der_read(0x30, private_key)?;
+                let (_version, rest) = der_read(0x02, ec_private_key)?;
+                let (secret, _) = der_read(0x04, rest)?;
+                Self::from_secret(network, KeyType::EccCompact, secret)
+            }
+            _ => bail!("Unsupported PKCS#8 private key algorithm"),
+        }
+    }
+
+    /// Constructs a keypair from an ed25519 (OKP) or P-256 (EC) JSON Web Key
+    /// holding a private key. The network has to be given.
+    pub fn from_jwk(jwk: &serde_json::Value, network: Network) -> Result<Self> {
+        let field = |name: &str| -> Result<Vec<u8>> {
+            match jwk[name].as_str() {
+                Some(value) => Ok(base64::decode_config(value, base64::URL_SAFE_NO_PAD)?),
+                None => bail!("JWK is missing the \"{}\" field", name),
+            }
+        };
+        let key_type = match (jwk["kty"].as_str(), jwk["crv"].as_str()) {
+            (Some("OKP"), Some("Ed25519")) => KeyType::Ed25519,
+            (Some("EC"), Some("P-256")) => KeyType::EccCompact,
+            _ => bail!("Only Ed25519 and P-256 JWKs are supported"),
+        };
+        let keypair = Self::from_secret(network, key_type, &field("d")?)?;
+        if field("x")? != keypair.public_key().to_bytes()[1..] {
+            bail!("Public key in JWK does not match its private key");
+        }
+        Ok(keypair)
+    }
+
+    fn from_secret(network: Network, key_type: KeyType, secret: &[u8]) -> Result<Self> {
+        if secret.len() != SECRET_KEY_LENGTH {
+            bail!("Invalid private key length {}", secret.len());
+        }
+        Self::generate_from_entropy(KeyTag { network, key_type }, secret)
+    }
+}
+
+const SECRET_KEY_LENGTH: usize = 32;
+
+const OID_ED25519: &[u8] = &[0x2b, 0x65, 0x70];
+const OID_EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+const OID_P256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+
+/// Encodes a DER tag, length and value
+fn der(tag: u8, value: &[u8]) -> Vec<u8> {
+    let mut result = vec![tag];
+    let len = value.len();
+    if len < 0x80 {
+        result.push(len as u8);
+    } else if len <= 0xff {
+        result.extend_from_slice(&[0x81, len as u8]);
+    } else {
+        result.extend_from_slice(&[0x82, (len >> 8) as u8, len as u8]);
+    }
+    result.extend_from_slice(value);
+    result
+}
+
+/// Reads a DER value with the given tag and returns its contents and the
+/// remaining input
+fn der_read(tag: u8, input: &[u8]) -> Result<(&[u8], &[u8])> {
+    let invalid = || anyhow!("Invalid DER encoding");
+    if input.first() != Some(&tag) {
+        return Err(invalid());
+    }
+    let (len, header) = match *input.get(1).ok_or_else(invalid)? {
+        len if len < 0x80 => (len as usize, 2),
+        0x81 => (*input.get(2).ok_or_else(invalid)? as usize, 3),
+        0x82 => {
+            let len = input.get(2..4).ok_or_else(invalid)?;
+            ((len[0] as usize) << 8 | len[1] as usize, 4)
+        }
+        _ => return Err(invalid()),
+    };
+    let value = input.get(header..header + len).ok_or_else(invalid)?;
+    Ok((value, &input[header + len..]))
+}
+
+/// Returns the x and y coordinates of the P-256 public key for the given
+/// private key. The compact public key of an ecc_compact keypair only holds
+/// the x coordinate.
+fn p256_public_point(secret: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+    use p256::elliptic_curve::sec1::ToEncodedPoint;
+    let secret_key = p256::SecretKey::from_bytes(secret)
+        .map_err(|_| anyhow!("Invalid ecc_compact private key"))?;
+    let point = secret_key.public_key().to_encoded_point(false);
+    match (point.x(), point.y()) {
+        (Some(x), Some(y)) => Ok((x.to_vec(), y.to_vec())),
+        _ => Err(anyhow!("Invalid ecc_compact public key")),
+    }
 }
 
 impl ReadWrite for Keypair {
@@ -115,4 +359,74 @@ mod tests {
             PublicKey::from_str(&pk.public_key().to_string()).expect("Failed to decode public key");
         assert_eq!(pk.public_key(), &decoded);
     }
+
+    #[test]
+    fn ed25519_pkcs8() {
+        let keypair = Keypair::generate(KeyTag {
+            network: Network::MainNet,
+            key_type: KeyType::Ed25519,
+        });
+        let der = keypair.to_pkcs8_der().expect("pkcs8");
+        assert_eq!(
+            hex::decode("302e020100300506032b657004220420").expect("prefix"),
+            der[..16].to_vec()
+        );
+        assert_eq!(keypair.to_bytes()[1..33].to_vec(), der[16..].to_vec());
+    }
+
+    #[test]
+    fn ecc_compact_jwk() {
+        let keypair = Keypair::generate(KeyTag {
+            network: Network::MainNet,
+            key_type: KeyType::EccCompact,
+        });
+        let jwk = keypair.to_jwk().expect("jwk");
+        assert_eq!("P-256", jwk["crv"]);
+        // The compact public key is the x coordinate
+        let x = base64::decode_config(jwk["x"].as_str().expect("x"), base64::URL_SAFE_NO_PAD)
+            .expect("x");
+        assert_eq!(keypair.public_key().to_bytes()[1..].to_vec(), x);
+        let der = keypair.to_pkcs8_der().expect("pkcs8");
+        assert_eq!(der.len(), 3 + der[2] as usize);
+    }
+
+    #[test]
+    fn roundtrip_export_import() {
+        for key_type in &[KeyType::Ed25519, KeyType::EccCompact] {
+            let keypair = Keypair::generate(KeyTag {
+                network: Network::TestNet,
+                key_type: *key_type,
+            });
+            let from_bytes = Keypair::from_tagged_bytes(&keypair.to_bytes()).expect("bytes");
+            assert_eq!(keypair, from_bytes);
+            let der = keypair.to_pkcs8_der().expect("pkcs8");
+            let from_der = Keypair::from_pkcs8_der(&der, Network::TestNet).expect("pkcs8");
+            assert_eq!(keypair, from_der);
+            let jwk = keypair.to_jwk().expect("jwk");
+            let from_jwk = Keypair::from_jwk(&jwk, Network::TestNet).expect("jwk");
+            assert_eq!(keypair, from_jwk);
+        }
+    }
+
+    #[test]
+    fn debug_redacts_secret() {
+        let tag = KeyTag {
+            network: Network::MainNet,
+            key_type: KeyType::Ed25519,
+        };
+        let keypair = Keypair::generate_from_entropy(tag, &[0x42; 32]).expect("keypair");
+        let secret = keypair.secret_bytes();
+        let debug = format!("{:?}", keypair);
+        let alternate = format!("{:#?}", keypair);
+        for secret in &[
+            hex::encode(&secret[..]),
+            format!("{:?}", &secret[..]),
+            "66, 66, 66, 66".to_string(),
+            "4242424242".to_string(),
+        ] {
+            assert!(!debug.contains(secret.as_str()));
+            assert!(!alternate.contains(secret.as_str()));
+        }
+        assert!(debug.starts_with("Keypair { public_key: "));
+    }
 }
@@ -0,0 +1,269 @@
+use crate::result::{anyhow, bail, Result};
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Environment variable to override the keyring directory with
+pub const KEYRING_ENV: &str = "HELIUM_WALLET_KEYRING";
+
+const DEFAULT_FILE: &str = "default";
+const WALLET_EXTENSION: &str = "key";
+
+/// A directory of named wallets. A basic wallet named `name` is stored as
+/// `name.key` and a set of shards as `name.key.1` through `name.key.N`. The
+/// name of the default wallet is stored in the `default` file.
+#[derive(Debug)]
+pub struct Keyring {
+    dir: PathBuf,
+}
+
+impl Keyring {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Opens the keyring of the current user. This is the directory given
+    /// by `HELIUM_WALLET_KEYRING` or `helium-wallet/wallets` in the XDG
+    /// config directory.
+    pub fn open() -> Result<Self> {
+        match env::var_os(KEYRING_ENV) {
+            Some(dir) => Ok(Self::new(PathBuf::from(dir))),
+            None => Ok(Self::new(config_dir()?.join("wallets"))),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The names of all wallets in the keyring in sorted order
+    pub fn names(&self) -> Result<Vec<String>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err.into()),
+        };
+        let mut names = vec![];
+        for entry in entries {
+            let file_name = entry?.file_name();
+            if let Some(name) = file_name.to_str().and_then(wallet_name) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// The wallet file or shard files of the named wallet
+    pub fn files(&self, name: &str) -> Result<Vec<PathBuf>> {
+        check_name(name)?;
+        let mut files = vec![];
+        let base = self.base_filename(name);
+        if base.is_file() {
+            files.push(base);
+        }
+        for shard in 1..=u8::MAX {
+            let shard_file = self.shard_filename(name, shard);
+            if !shard_file.is_file() {
+                break;
+            }
+            files.push(shard_file);
+        }
+        if files.is_empty() {
+            bail!("No wallet named \"{}\" in the keyring", name);
+        }
+        Ok(files)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.files(name).is_ok()
+    }
+
+    /// Copies the given wallet file, or the given shard files, into the
+    /// keyring under the given name
+    pub fn add(&self, name: &str, files: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        check_name(name)?;
+        if self.contains(name) {
+            bail!("A wallet named \"{}\" already exists in the keyring", name);
+        }
+        let targets = match files.len() {
+            0 => bail!("At least one wallet file expected"),
+            1 => vec![self.base_filename(name)],
+            n if n <= u8::MAX as usize => (1..=n as u8)
+                .map(|shard| self.shard_filename(name, shard))
+                .collect(),
+            _ => bail!("Too many wallet files"),
+        };
+        self.create_dir()?;
+        for (file, target) in files.iter().zip(&targets) {
+            fs::copy(file, target)?;
+            restrict_permissions(target)?;
+        }
+        Ok(targets)
+    }
+
+    /// Removes the files of the named wallet. The default wallet setting is
+    /// cleared if it names the removed wallet.
+    pub fn remove(&self, name: &str) -> Result {
+        for file in self.files(name)? {
+            fs::remove_file(file)?;
+        }
+        if self.default_name()?.as_deref() == Some(name) {
+            fs::remove_file(self.dir.join(DEFAULT_FILE))?;
+        }
+        Ok(())
+    }
+
+    /// Renames a wallet, keeping it the default wallet if it was
+    pub fn rename(&self, from: &str, to: &str) -> Result {
+        check_name(to)?;
+        if self.contains(to) {
+            bail!("A wallet named \"{}\" already exists in the keyring", to);
+        }
+        for file in self.files(from)? {
+            let file_name = file
+                .file_name()
+                .and_then(|file_name| file_name.to_str())
+                .ok_or_else(|| anyhow!("Invalid wallet file name {}", file.display()))?;
+            let target = self.dir.join(format!("{}{}", to, &file_name[from.len()..]));
+            fs::rename(&file, target)?;
+        }
+        if self.default_name()?.as_deref() == Some(from) {
+            self.set_default(to)?;
+        }
+        Ok(())
+    }
+
+    /// The name of the default wallet, if one is set
+    pub fn default_name(&self) -> Result<Option<String>> {
+        match fs::read_to_string(self.dir.join(DEFAULT_FILE)) {
+            Ok(name) => Ok(Some(name.trim().to_string())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn set_default(&self, name: &str) -> Result {
+        self.files(name)?;
+        self.create_dir()?;
+        fs::write(self.dir.join(DEFAULT_FILE), format!("{}\n", name))?;
+        Ok(())
+    }
+
+    fn base_filename(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", name, WALLET_EXTENSION))
+    }
+
+    fn shard_filename(&self, name: &str, shard: u8) -> PathBuf {
+        self.dir
+            .join(format!("{}.{}.{}", name, WALLET_EXTENSION, shard))
+    }
+
+    fn create_dir(&self) -> Result {
+        fs::create_dir_all(&self.dir)?;
+        restrict_permissions(&self.dir)
+    }
+}
+
+/// The `helium-wallet` directory in the XDG config directory of the
+/// current user
+pub fn config_dir() -> Result<PathBuf> {
+    let config_dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => match env::var_os("HOME") {
+            Some(home) => Path::new(&home).join(".config"),
+            None => bail!("Could not find the config directory. Set XDG_CONFIG_HOME"),
+        },
+    };
+    Ok(config_dir.join("helium-wallet"))
+}
+
+/// Checks that a wallet name can be used as a file name in the keyring
+fn check_name(name: &str) -> Result {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    if !valid {
+        bail!(
+            "Invalid wallet name \"{}\". Use letters, digits, '-', '_' and '.'",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// The wallet name of a file in the keyring directory, if it is a wallet
+/// or shard file
+fn wallet_name(file_name: &str) -> Option<String> {
+    let name = match file_name.rsplit_once('.') {
+        Some((base, shard)) if !shard.is_empty() && shard.chars().all(|c| c.is_ascii_digit()) => {
+            base
+        }
+        _ => file_name,
+    };
+    name.strip_suffix(&format!(".{}", WALLET_EXTENSION))
+        .filter(|name| check_name(name).is_ok())
+        .map(|name| name.to_string())
+}
+
+/// Makes keyring files and directories only accessible by their owner on
+/// unix systems
+fn restrict_permissions(path: &Path) -> Result {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = if path.is_dir() { 0o700 } else { 0o600 };
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyring_wallets() {
+        let dir = env::temp_dir().join(format!("helium-wallet-keyring-{}", std::process::id()));
+        let source = env::temp_dir().join(format!("helium-wallet-source-{}", std::process::id()));
+        fs::create_dir_all(&source).expect("source dir");
+        let files: Vec<PathBuf> = (1..=3)
+            .map(|i| {
+                let file = source.join(format!("wallet.key.{}", i));
+                fs::write(&file, [i]).expect("wallet file");
+                file
+            })
+            .collect();
+
+        let keyring = Keyring::new(dir.clone());
+        assert!(keyring.names().expect("names").is_empty());
+        keyring.add("hot", &files[..1]).expect("add basic");
+        keyring.add("cold", &files).expect("add shards");
+        assert!(keyring.add("hot", &files[..1]).is_err());
+        assert!(keyring.add("../hot", &files[..1]).is_err());
+        assert_eq!(vec!["cold", "hot"], keyring.names().expect("names"));
+        assert_eq!(3, keyring.files("cold").expect("files").len());
+
+        keyring.set_default("cold").expect("set default");
+        keyring.rename("cold", "vault").expect("rename");
+        assert_eq!(
+            Some("vault".to_string()),
+            keyring.default_name().expect("default")
+        );
+        assert_eq!(3, keyring.files("vault").expect("files").len());
+
+        keyring.remove("vault").expect("remove");
+        assert_eq!(None, keyring.default_name().expect("default"));
+        assert_eq!(vec!["hot"], keyring.names().expect("names"));
+
+        fs::remove_dir_all(dir).expect("remove keyring");
+        fs::remove_dir_all(source).expect("remove source");
+    }
+}
@@ -3,11 +3,20 @@ extern crate lazy_static;
 #[macro_use]
 extern crate prettytable;
 
+#[cfg(unix)]
+pub mod agent;
 pub mod cmd;
+pub mod config;
+pub mod contacts;
 pub mod format;
+pub mod hd;
 pub mod keypair;
+pub mod keyring;
 pub mod memo;
+pub mod message;
+pub mod metadata;
 pub mod mnemonic;
+pub mod password_source;
 pub mod pwhash;
 pub mod result;
 pub mod staking;
@@ -1,7 +1,10 @@
+#[cfg(unix)]
+use helium_wallet::cmd::agent;
 use helium_wallet::{
     cmd::{
-        balance, burn, commit, create, hotspots, htlc, info, multisig, oracle, oui, pay, request,
-        securities, upgrade, validators, vars, verify, Opts,
+        balance, burn, commit, config, contacts, create, export, hd, hotspots, htlc, import, info,
+        message, multisig, oracle, oui, password, pay, pwhash, request, reshard, securities, slots,
+        txn, upgrade, validators, vars, verify, wallets, Opts,
     },
     result::Result,
 };
@@ -21,10 +24,23 @@ pub struct Cli {
 pub enum Cmd {
     Info(info::Cmd),
     Verify(verify::Cmd),
+    Message(message::Cmd),
     Balance(balance::Cmd),
     Hotspots(Box<hotspots::Cmd>),
     Create(create::Cmd),
+    Export(export::Cmd),
+    Import(import::Cmd),
+    Hd(hd::Cmd),
     Upgrade(upgrade::Cmd),
+    Reshard(reshard::Cmd),
+    Password(password::Cmd),
+    Config(config::Cmd),
+    #[cfg(unix)]
+    Agent(agent::Cmd),
+    Wallets(wallets::Cmd),
+    Contacts(contacts::Cmd),
+    Slots(slots::Cmd),
+    Pwhash(pwhash::Cmd),
     Pay(Box<pay::Cmd>),
     Htlc(htlc::Cmd),
     Oui(oui::Cmd),
@@ -32,6 +48,7 @@ pub enum Cmd {
     Securities(securities::Cmd),
     Burn(burn::Cmd),
     Multisig(multisig::Cmd),
+    Txn(txn::Cmd),
     Request(request::Cmd),
     Vars(vars::Cmd),
     Validators(validators::Cmd),
@@ -48,23 +65,54 @@ async fn main() {
 }
 
 async fn run(cli: Cli) -> Result {
+    let opts = cli.opts.load_config()?;
+    // Commands that create a new wallet file or do not use a wallet file at
+    // all, like config, keyring and address book management, must not fail
+    // on a missing default wallet
+    let opts = match cli.cmd {
+        Cmd::Config(_)
+        | Cmd::Wallets(_)
+        | Cmd::Contacts(_)
+        | Cmd::Create(_)
+        | Cmd::Import(_)
+        | Cmd::Hd(_)
+        | Cmd::Pwhash(_)
+        | Cmd::Message(message::Cmd::Encrypt(_))
+        | Cmd::Message(message::Cmd::Verify(_))
+        | Cmd::Txn(txn::Cmd::Decode(_)) => opts,
+        _ => opts.resolve_wallet()?,
+    };
     match cli.cmd {
-        Cmd::Info(cmd) => cmd.run(cli.opts).await,
-        Cmd::Verify(cmd) => cmd.run(cli.opts).await,
-        Cmd::Balance(cmd) => cmd.run(cli.opts).await,
-        Cmd::Hotspots(cmd) => cmd.run(cli.opts).await,
-        Cmd::Create(cmd) => cmd.run(cli.opts).await,
-        Cmd::Upgrade(cmd) => cmd.run(cli.opts).await,
-        Cmd::Pay(cmd) => cmd.run(cli.opts).await,
-        Cmd::Htlc(cmd) => cmd.run(cli.opts).await,
-        Cmd::Oui(cmd) => cmd.run(cli.opts).await,
-        Cmd::Oracle(cmd) => cmd.run(cli.opts).await,
-        Cmd::Securities(cmd) => cmd.run(cli.opts).await,
-        Cmd::Burn(cmd) => cmd.run(cli.opts).await,
-        Cmd::Multisig(cmd) => cmd.run(cli.opts).await,
-        Cmd::Request(cmd) => cmd.run(cli.opts).await,
-        Cmd::Vars(cmd) => cmd.run(cli.opts).await,
-        Cmd::Validators(cmd) => cmd.run(cli.opts).await,
-        Cmd::Commit(cmd) => cmd.run(cli.opts).await,
+        Cmd::Info(cmd) => cmd.run(opts).await,
+        Cmd::Verify(cmd) => cmd.run(opts).await,
+        Cmd::Message(cmd) => cmd.run(opts).await,
+        Cmd::Balance(cmd) => cmd.run(opts).await,
+        Cmd::Hotspots(cmd) => cmd.run(opts).await,
+        Cmd::Create(cmd) => cmd.run(opts).await,
+        Cmd::Export(cmd) => cmd.run(opts).await,
+        Cmd::Import(cmd) => cmd.run(opts).await,
+        Cmd::Hd(cmd) => cmd.run(opts).await,
+        Cmd::Upgrade(cmd) => cmd.run(opts).await,
+        Cmd::Reshard(cmd) => cmd.run(opts).await,
+        Cmd::Password(cmd) => cmd.run(opts).await,
+        Cmd::Config(cmd) => cmd.run(opts).await,
+        #[cfg(unix)]
+        Cmd::Agent(cmd) => cmd.run(opts).await,
+        Cmd::Wallets(cmd) => cmd.run(opts).await,
+        Cmd::Contacts(cmd) => cmd.run(opts).await,
+        Cmd::Slots(cmd) => cmd.run(opts).await,
+        Cmd::Pwhash(cmd) => cmd.run(opts).await,
+        Cmd::Pay(cmd) => cmd.run(opts).await,
+        Cmd::Htlc(cmd) => cmd.run(opts).await,
+        Cmd::Oui(cmd) => cmd.run(opts).await,
+        Cmd::Oracle(cmd) => cmd.run(opts).await,
+        Cmd::Securities(cmd) => cmd.run(opts).await,
+        Cmd::Burn(cmd) => cmd.run(opts).await,
+        Cmd::Multisig(cmd) => cmd.run(opts).await,
+        Cmd::Txn(cmd) => cmd.run(opts).await,
+        Cmd::Request(cmd) => cmd.run(opts).await,
+        Cmd::Vars(cmd) => cmd.run(opts).await,
+        Cmd::Validators(cmd) => cmd.run(opts).await,
+        Cmd::Commit(cmd) => cmd.run(opts).await,
     }
 }
@@ -0,0 +1,260 @@
+use crate::{
+    keypair::{KeyType, Keypair, PublicKey, Verify},
+    result::{anyhow, bail, Result},
+};
+use serde_derive::{Deserialize, Serialize};
+use sodiumoxide::crypto::{box_, sealedbox, sign::ed25519};
+use std::{fmt, str::FromStr};
+use zeroize::Zeroizing;
+
+/// Prefix of every signed message. The prefix separates message signatures
+/// from transaction signatures, so a partner can not get a transaction
+/// signed by passing it off as a challenge.
+pub const MESSAGE_PREFIX: &[u8] = b"\x19Helium Signed Message:\n";
+
+/// How the message of a signed message is given
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Hex,
+    Base64,
+}
+
+impl Encoding {
+    /// Decodes a message given in this encoding into the bytes to sign
+    pub fn decode(&self, message: &str) -> Result<Vec<u8>> {
+        match self {
+            Self::Utf8 => Ok(message.as_bytes().to_vec()),
+            Self::Hex => Ok(hex::decode(message)?),
+            Self::Base64 => Ok(base64::decode(message)?),
+        }
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = crate::result::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "utf8" => Ok(Self::Utf8),
+            "hex" => Ok(Self::Hex),
+            "base64" => Ok(Self::Base64),
+            _ => bail!("Invalid message encoding {}", s),
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Utf8 => f.write_str("utf8"),
+            Self::Hex => f.write_str("hex"),
+            Self::Base64 => f.write_str("base64"),
+        }
+    }
+}
+
+/// A message with the address that signed it and the base64 encoded
+/// signature. This is the json envelope exchanged with partners.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct SignedMessage {
+    pub address: String,
+    pub message: String,
+    #[serde(default)]
+    pub encoding: Encoding,
+    pub signature: String,
+}
+
+impl SignedMessage {
+    pub fn sign(keypair: &Keypair, message: &str, encoding: Encoding) -> Result<Self> {
+        let signature = keypair.sign(&payload(&encoding.decode(message)?))?;
+        Ok(Self {
+            address: keypair.public_key().to_string(),
+            message: message.to_string(),
+            encoding,
+            signature: base64::encode(&signature),
+        })
+    }
+
+    /// Checks that the signature of the message was made by the key of the
+    /// address
+    pub fn verify(&self) -> Result {
+        let public_key: PublicKey = self.address.parse()?;
+        let message = self.encoding.decode(&self.message)?;
+        public_key
+            .verify(&payload(&message), &self.signature()?)
+            .map_err(|_| anyhow!("Invalid signature for {}", self.address))
+    }
+
+    pub fn signature(&self) -> Result<Vec<u8>> {
+        decode_signature(&self.signature)
+    }
+}
+
+/// Decodes a hex or base64 encoded signature
+pub fn decode_signature(signature: &str) -> Result<Vec<u8>> {
+    match hex::decode(signature) {
+        Ok(signature) => Ok(signature),
+        Err(_) => base64::decode(signature)
+            .map_err(|_| anyhow!("Signature is neither hex nor base64 encoded")),
+    }
+}
+
+fn payload(message: &[u8]) -> Vec<u8> {
+    [MESSAGE_PREFIX, message].concat()
+}
+
+const ARMOR_BEGIN: &str = "-----BEGIN HELIUM ENCRYPTED MESSAGE-----";
+const ARMOR_END: &str = "-----END HELIUM ENCRYPTED MESSAGE-----";
+const ARMOR_TO: &str = "To: ";
+const ARMOR_LINE_LENGTH: usize = 64;
+
+/// Encrypts a message to the owner of an ed25519 address. The address is
+/// converted to an X25519 key and the message is put in a libsodium sealed
+/// box, which only the private key of the address can open. The result is
+/// armored text that can be pasted into chat or email.
+pub fn encrypt(to: &PublicKey, message: &[u8]) -> Result<String> {
+    init()?;
+    let sealed = sealedbox::seal(message, &x25519_public_key(to)?);
+    let mut armored = format!("{}\n{}{}\n\n", ARMOR_BEGIN, ARMOR_TO, to);
+    let encoded = base64::encode(&sealed);
+    for line in encoded.as_bytes().chunks(ARMOR_LINE_LENGTH) {
+        armored.push_str(&String::from_utf8_lossy(line));
+        armored.push('\n');
+    }
+    armored.push_str(ARMOR_END);
+    armored.push('\n');
+    Ok(armored)
+}
+
+/// The recipient address of an armored message
+pub fn encrypted_to(armored: &str) -> Result<PublicKey> {
+    let (to, _) = dearmor(armored)?;
+    Ok(to.parse()?)
+}
+
+/// Decrypts an armored message with the keypair of its recipient
+pub fn decrypt(keypair: &Keypair, armored: &str) -> Result<Zeroizing<Vec<u8>>> {
+    init()?;
+    let (to, sealed) = dearmor(armored)?;
+    if to != keypair.public_key().to_string() {
+        bail!("Message is encrypted to {}, not this wallet", to);
+    }
+    let public_key = x25519_public_key(keypair.public_key())?;
+    let secret_key = x25519_secret_key(keypair)?;
+    sealedbox::open(&sealed, &public_key, &secret_key)
+        .map(Zeroizing::new)
+        .map_err(|_| anyhow!("Failed to decrypt message"))
+}
+
+/// Returns the recipient and sealed box of an armored message. Text around
+/// the armor and indentation, as added by some chat clients, are ignored.
+fn dearmor(armored: &str) -> Result<(String, Vec<u8>)> {
+    let mut lines = armored
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| *line != ARMOR_BEGIN);
+    if lines.next().is_none() {
+        bail!("No encrypted message found");
+    }
+    let mut to = None;
+    for line in &mut lines {
+        if line.is_empty() {
+            break;
+        }
+        if let Some(address) = line.strip_prefix(ARMOR_TO) {
+            to = Some(address.to_string());
+        }
+    }
+    let mut encoded = String::new();
+    let mut complete = false;
+    for line in lines {
+        if line == ARMOR_END {
+            complete = true;
+            break;
+        }
+        encoded.push_str(line);
+    }
+    if !complete {
+        bail!("Encrypted message is incomplete");
+    }
+    let to = to.ok_or_else(|| anyhow!("Encrypted message has no recipient"))?;
+    Ok((to, base64::decode(&encoded)?))
+}
+
+fn x25519_public_key(public_key: &PublicKey) -> Result<box_::PublicKey> {
+    if public_key.key_tag().key_type != KeyType::Ed25519 {
+        bail!("Encryption is only supported for ed25519 addresses");
+    }
+    ed25519::PublicKey::from_slice(&public_key.to_vec()[1..])
+        .and_then(|key| ed25519::to_curve25519_pk(&key).ok())
+        .ok_or_else(|| anyhow!("Invalid ed25519 address {}", public_key))
+}
+
+fn x25519_secret_key(keypair: &Keypair) -> Result<box_::SecretKey> {
+    if keypair.key_type() != KeyType::Ed25519 {
+        bail!("Decryption is only supported for ed25519 wallets");
+    }
+    let seed = ed25519::Seed::from_slice(&keypair.secret_bytes())
+        .ok_or_else(|| anyhow!("Invalid ed25519 key"))?;
+    let (_, secret_key) = ed25519::keypair_from_seed(&seed);
+    ed25519::to_curve25519_sk(&secret_key).map_err(|_| anyhow!("Invalid ed25519 key"))
+}
+
+fn init() -> Result {
+    sodiumoxide::init().map_err(|_| anyhow!("Failed to initialize sodium"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_verify() {
+        let keypair = Keypair::default();
+        let signed =
+            SignedMessage::sign(&keypair, "challenge 42", Encoding::Utf8).expect("sign utf8");
+        signed.verify().expect("verify utf8");
+
+        let json = serde_json::to_string(&signed).expect("to json");
+        let parsed: SignedMessage = serde_json::from_str(&json).expect("from json");
+        assert_eq!(signed, parsed);
+
+        let hex_signed = SignedMessage {
+            signature: hex::encode(signed.signature().expect("signature")),
+            ..signed.clone()
+        };
+        hex_signed.verify().expect("verify hex signature");
+
+        let tampered = SignedMessage {
+            message: "challenge 43".to_string(),
+            ..signed
+        };
+        assert!(tampered.verify().is_err());
+
+        let hex = SignedMessage::sign(&keypair, "00ff", Encoding::Hex).expect("sign hex");
+        hex.verify().expect("verify hex");
+        assert!(SignedMessage::sign(&keypair, "0g", Encoding::Hex).is_err());
+    }
+
+    #[test]
+    fn encrypt_decrypt() {
+        let keypair = Keypair::default();
+        let armored = encrypt(keypair.public_key(), b"meet at noon").expect("encrypt");
+        assert_eq!(
+            keypair.public_key(),
+            &encrypted_to(&armored).expect("recipient")
+        );
+        // Chat clients may quote or indent pasted text
+        let pasted = format!("see below\n{}", armored.replace('\n', "\n  "));
+        assert_eq!(
+            b"meet at noon".to_vec(),
+            *decrypt(&keypair, &pasted).expect("decrypt")
+        );
+        assert!(decrypt(&Keypair::default(), &armored).is_err());
+        let truncated = armored.replace(ARMOR_END, "");
+        assert!(decrypt(&keypair, &truncated).is_err());
+    }
+}
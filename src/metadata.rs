@@ -0,0 +1,173 @@
+use crate::{
+    hd::DerivationPath,
+    result::{bail, Result},
+};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::{
+    io::{self, Read},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const FIELD_LABEL: u8 = 1;
+const FIELD_CREATED_AT: u8 = 2;
+const FIELD_NOTES: u8 = 3;
+const FIELD_DERIVATION_PATH: u8 = 4;
+
+/// Descriptive information stored with a wallet. The metadata is not
+/// encrypted but is authenticated together with the encrypted key, so it can
+/// not be changed without the wallet failing to decrypt.
+///
+/// The metadata block is a little endian u16 length followed by fields, each
+/// a u8 field kind, a little endian u16 length and the field value.
+/// Fields are written in ascending kind order. Unknown field kinds, from
+/// newer releases, are kept as is so the block still authenticates.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    pub label: Option<String>,
+    /// Creation time in seconds since the unix epoch
+    pub created_at: Option<u64>,
+    pub notes: Option<String>,
+    /// The derivation path of a key derived from a seed phrase
+    pub derivation_path: Option<DerivationPath>,
+    /// Fields of kinds this release does not know, as kind and value
+    pub unknown_fields: Vec<(u8, Vec<u8>)>,
+}
+
+impl Metadata {
+    /// Returns metadata with the creation time set to the current time
+    pub fn now() -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .ok();
+        Self {
+            created_at,
+            ..Default::default()
+        }
+    }
+
+    /// The creation time formatted as an RFC 3339 UTC timestamp
+    pub fn created_at_str(&self) -> Option<String> {
+        self.created_at.map(|created_at| {
+            let time = UNIX_EPOCH + Duration::from_secs(created_at);
+            chrono::DateTime::<chrono::Utc>::from(time)
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+        })
+    }
+
+    pub fn read(reader: &mut dyn io::Read) -> Result<Self> {
+        let len = reader.read_u16::<LittleEndian>()?;
+        let mut fields = reader.take(len as u64);
+        let mut metadata = Self::default();
+        while fields.limit() > 0 {
+            let kind = fields.read_u8()?;
+            let len = fields.read_u16::<LittleEndian>()?;
+            let mut value = vec![0u8; len as usize];
+            fields.read_exact(&mut value)?;
+            match kind {
+                FIELD_LABEL => metadata.label = Some(String::from_utf8(value)?),
+                FIELD_CREATED_AT => {
+                    let mut value = &value[..];
+                    metadata.created_at = Some(value.read_u64::<LittleEndian>()?);
+                    if !value.is_empty() {
+                        bail!("Invalid wallet creation time");
+                    }
+                }
+                FIELD_NOTES => metadata.notes = Some(String::from_utf8(value)?),
+                FIELD_DERIVATION_PATH => {
+                    metadata.derivation_path = Some(String::from_utf8(value)?.parse()?)
+                }
+                _ => metadata.unknown_fields.push((kind, value)),
+            }
+        }
+        Ok(metadata)
+    }
+
+    pub fn write(&self, writer: &mut dyn io::Write) -> Result {
+        writer.write_all(&self.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Encodes the metadata block as it is written to the wallet file and
+    /// authenticated with the encrypted key
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut values: Vec<(u8, Vec<u8>)> = vec![];
+        if let Some(label) = &self.label {
+            values.push((FIELD_LABEL, label.as_bytes().to_vec()));
+        }
+        if let Some(created_at) = self.created_at {
+            values.push((FIELD_CREATED_AT, created_at.to_le_bytes().to_vec()));
+        }
+        if let Some(notes) = &self.notes {
+            values.push((FIELD_NOTES, notes.as_bytes().to_vec()));
+        }
+        if let Some(derivation_path) = &self.derivation_path {
+            values.push((
+                FIELD_DERIVATION_PATH,
+                derivation_path.to_string().into_bytes(),
+            ));
+        }
+        values.extend(self.unknown_fields.iter().cloned());
+        // A stable sort keeps repeated unknown fields in their read order
+        values.sort_by_key(|(kind, _)| *kind);
+        let mut fields = vec![];
+        for (kind, value) in &values {
+            Self::write_field(&mut fields, *kind, value)?;
+        }
+        let mut bytes = vec![];
+        bytes.write_u16::<LittleEndian>(Self::field_len(fields.len())?)?;
+        bytes.extend_from_slice(&fields);
+        Ok(bytes)
+    }
+
+    fn write_field(writer: &mut Vec<u8>, kind: u8, value: &[u8]) -> Result {
+        writer.write_u8(kind)?;
+        writer.write_u16::<LittleEndian>(Self::field_len(value.len())?)?;
+        writer.extend_from_slice(value);
+        Ok(())
+    }
+
+    fn field_len(len: usize) -> Result<u16> {
+        if len > u16::MAX as usize {
+            bail!("Wallet metadata too long");
+        }
+        Ok(len as u16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn roundtrip_metadata() {
+        let metadata = Metadata {
+            label: Some("payments".to_string()),
+            created_at: Some(1_600_000_000),
+            notes: Some("hot wallet, ask ops before use".to_string()),
+            derivation_path: Some(DerivationPath::account(2).expect("derivation path")),
+            unknown_fields: vec![],
+        };
+        let bytes = metadata.to_bytes().expect("metadata bytes");
+        let decoded = Metadata::read(&mut Cursor::new(&bytes)).expect("metadata");
+        assert_eq!(metadata, decoded);
+        assert_eq!(
+            Some("2020-09-13T12:26:40Z".to_string()),
+            decoded.created_at_str()
+        );
+
+        let empty = Metadata::default().to_bytes().expect("metadata bytes");
+        assert_eq!(vec![0, 0], empty);
+    }
+
+    #[test]
+    fn unknown_metadata_fields() {
+        // A label followed by a field of an unknown kind 9
+        let bytes = vec![10, 0, 1, 2, 0, b'h', b'i', 9, 2, 0, 0xab, 0xcd];
+        let decoded = Metadata::read(&mut Cursor::new(&bytes)).expect("metadata");
+        assert_eq!(Some("hi".to_string()), decoded.label);
+        assert_eq!(vec![(9, vec![0xab, 0xcd])], decoded.unknown_fields);
+        assert_eq!(bytes, decoded.to_bytes().expect("metadata bytes"));
+    }
+}
@@ -1,13 +1,27 @@
 use crate::result::{bail, Result};
 
+use hmac::Hmac;
 use regex::Regex;
-use sha2::{Digest, Sha256};
+use sha2::{Digest, Sha256, Sha512};
 use structopt::{clap::arg_enum, StructOpt};
+use unicode_normalization::UnicodeNormalization;
+use zeroize::Zeroizing;
 
+include!(concat!(env!("OUT_DIR"), "/chinese_simplified.rs"));
+include!(concat!(env!("OUT_DIR"), "/chinese_traditional.rs"));
+include!(concat!(env!("OUT_DIR"), "/czech.rs"));
 include!(concat!(env!("OUT_DIR"), "/english.rs"));
+include!(concat!(env!("OUT_DIR"), "/french.rs"));
+include!(concat!(env!("OUT_DIR"), "/italian.rs"));
+include!(concat!(env!("OUT_DIR"), "/japanese.rs"));
+include!(concat!(env!("OUT_DIR"), "/korean.rs"));
+include!(concat!(env!("OUT_DIR"), "/portuguese.rs"));
+include!(concat!(env!("OUT_DIR"), "/spanish.rs"));
 
 type WordList = &'static [&'static str];
 
+const BIP39_SEED_ROUNDS: u32 = 2048;
+
 arg_enum! {
     #[derive( Debug, StructOpt)]
     pub enum SeedType {
@@ -16,29 +30,253 @@ arg_enum! {
     }
 }
 
-pub enum Language {
-    English,
+arg_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Language {
+        English,
+        ChineseSimplified,
+        ChineseTraditional,
+        Czech,
+        French,
+        Italian,
+        Japanese,
+        Korean,
+        Portuguese,
+        Spanish,
+    }
 }
 
+const LANGUAGES: &[Language] = &[
+    Language::English,
+    Language::ChineseSimplified,
+    Language::ChineseTraditional,
+    Language::Czech,
+    Language::French,
+    Language::Italian,
+    Language::Japanese,
+    Language::Korean,
+    Language::Portuguese,
+    Language::Spanish,
+];
+
 fn get_wordlist(language: Language) -> WordList {
     match language {
         Language::English => WORDS_ENGLISH,
+        Language::ChineseSimplified => WORDS_CHINESE_SIMPLIFIED,
+        Language::ChineseTraditional => WORDS_CHINESE_TRADITIONAL,
+        Language::Czech => WORDS_CZECH,
+        Language::French => WORDS_FRENCH,
+        Language::Italian => WORDS_ITALIAN,
+        Language::Japanese => WORDS_JAPANESE,
+        Language::Korean => WORDS_KOREAN,
+        Language::Portuguese => WORDS_PORTUGUESE,
+        Language::Spanish => WORDS_SPANISH,
+    }
+}
+
+/// Minimum length of a word prefix that is accepted in place of the full
+/// word. BIP39 wordlists are chosen so that the first four letters of a
+/// word identify it.
+const WORD_PREFIX_LENGTH: usize = 4;
+
+/// Maximum edit distance of suggested replacements for an unknown word
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Maximum number of suggested replacements for an unknown word
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Returns the index of the given word in the wordlist. Words are compared
+/// after NFKD normalization, as BIP39 requires, so precomposed and
+/// decomposed accented characters match. A prefix of at least four letters
+/// is accepted if only one word in the wordlist starts with it.
+fn find_word(wordlist: WordList, word: &str) -> Option<usize> {
+    let word: Vec<char> = word.to_lowercase().nfkd().collect();
+    if let Some(index) = wordlist
+        .iter()
+        .position(|s| s.nfkd().eq(word.iter().copied()))
+    {
+        return Some(index);
+    }
+    if word.len() < WORD_PREFIX_LENGTH {
+        return None;
+    }
+    let mut matches = wordlist
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.nfkd().take(word.len()).eq(word.iter().copied()));
+    match (matches.next(), matches.next()) {
+        (Some((index, _)), None) => Some(index),
+        _ => None,
+    }
+}
+
+/// Detects the language of the given mnemonic. A language matches if all
+/// words are in its wordlist. Since some wordlists share words, the
+/// checksum is used to pick between multiple matching languages. If no
+/// language has all the words the language with the most matching words is
+/// returned, so that the words which are not found can be reported.
+pub fn detect_language(words: &[String], seed_type: &SeedType) -> Result<Language> {
+    let found_count = |language: Language| {
+        let wordlist = get_wordlist(language);
+        words
+            .iter()
+            .filter(|word| find_word(wordlist, word).is_some())
+            .count()
+    };
+    let candidates: Vec<Language> = LANGUAGES
+        .iter()
+        .copied()
+        .filter(|language| found_count(*language) == words.len())
+        .collect();
+    let valid: Vec<Language> = match candidates.len() {
+        0 => {
+            // max_by_key picks the last maximum, so search in reverse to
+            // prefer the first language on a tie
+            let best = LANGUAGES.iter().rev().max_by_key(|l| found_count(**l));
+            return Ok(*best.expect("languages"));
+        }
+        1 => return Ok(candidates[0]),
+        _ => candidates
+            .iter()
+            .copied()
+            .filter(|language| decode_entropy(words, seed_type, *language).is_ok())
+            .collect(),
+    };
+    match valid.as_slice() {
+        [language] => Ok(*language),
+        [] => Ok(candidates[0]),
+        _ => bail!("Seed phrase language is ambiguous. Specify the language to use."),
     }
 }
 
+/// Returns the positions of the words that are not in the wordlist of the
+/// given language
+pub fn unknown_words(words: &[String], language: Language) -> Vec<usize> {
+    let wordlist = get_wordlist(language);
+    words
+        .iter()
+        .enumerate()
+        .filter(|(_, word)| find_word(wordlist, word).is_none())
+        .map(|(position, _)| position)
+        .collect()
+}
+
+/// Suggests the wordlist entries closest to the given unknown word by edit
+/// distance, closest first
+pub fn suggest_words(word: &str, language: Language) -> Vec<&'static str> {
+    let word: Vec<char> = word.to_lowercase().nfkd().collect();
+    let mut suggestions: Vec<(usize, &'static str)> = get_wordlist(language)
+        .iter()
+        .map(|s| (edit_distance(&word, &s.nfkd().collect::<Vec<char>>()), *s))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .collect();
+    suggestions.sort_by_key(|(distance, _)| *distance);
+    suggestions
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, s)| s)
+        .collect()
+}
+
+/// Returns the single word replacements that give the mnemonic a valid
+/// checksum, as pairs of word position and replacement word. Replacements
+/// that are closer to the word they replace by edit distance come first.
+pub fn checksum_corrections(
+    words: &[String],
+    seed_type: &SeedType,
+    language: Language,
+) -> Vec<(usize, &'static str)> {
+    let wordlist = get_wordlist(language);
+    let mut indices = match find_indices(words, language) {
+        Ok(indices) => indices,
+        Err(_) => return vec![],
+    };
+    let mut corrections = vec![];
+    for position in 0..indices.len() {
+        let original = indices[position];
+        let original_chars: Vec<char> = wordlist[original].nfkd().collect();
+        for (index, word) in wordlist.iter().enumerate() {
+            if index == original {
+                continue;
+            }
+            indices[position] = index;
+            if indices_to_entropy(&indices, seed_type).is_ok() {
+                let chars: Vec<char> = word.nfkd().collect();
+                let distance = edit_distance(&original_chars, &chars);
+                corrections.push((distance, position, *word));
+            }
+        }
+        indices[position] = original;
+    }
+    corrections.sort_by_key(|(distance, position, _)| (*distance, *position));
+    corrections
+        .into_iter()
+        .map(|(_, position, word)| (position, word))
+        .collect()
+}
+
+/// Replaces each word, which may be a unique prefix, with the full word from
+/// the wordlist of the given language
+pub fn expand_words(words: &[String], language: Language) -> Result<Vec<String>> {
+    let wordlist = get_wordlist(language);
+    Ok(find_indices(words, language)?
+        .into_iter()
+        .map(|index| wordlist[index].to_string())
+        .collect())
+}
+
 /// Converts a 12 or 24 word mnemonic to entropy that can be used to
-/// generate a keypair
-pub fn mnemonic_to_entropy(words: Vec<String>, seed_type: &SeedType) -> Result<[u8; 32]> {
+/// generate a keypair. The language of the mnemonic is detected when not
+/// given.
+pub fn mnemonic_to_entropy(
+    words: Vec<String>,
+    seed_type: &SeedType,
+    language: Option<Language>,
+) -> Result<[u8; 32]> {
+    let language = match language {
+        Some(language) => language,
+        None => detect_language(&words, seed_type)?,
+    };
+    decode_entropy(&words, seed_type, language)
+}
+
+fn decode_entropy(words: &[String], seed_type: &SeedType, language: Language) -> Result<[u8; 32]> {
+    indices_to_entropy(&find_indices(words, language)?, seed_type)
+}
+
+fn find_indices(words: &[String], language: Language) -> Result<Vec<usize>> {
+    let wordlist = get_wordlist(language);
+    let mut indices = Vec::with_capacity(words.len());
+    for word in words.iter() {
+        match find_word(wordlist, word) {
+            Some(index) => indices.push(index),
+            None => {
+                let suggestions = suggest_words(word, language);
+                if suggestions.is_empty() {
+                    bail!("Seed word {} not found in wordlist", word);
+                }
+                bail!(
+                    "Seed word {} not found in wordlist. Did you mean {}?",
+                    word,
+                    suggestions.join(", ")
+                );
+            }
+        }
+    }
+    Ok(indices)
+}
+
+fn indices_to_entropy(indices: &[usize], seed_type: &SeedType) -> Result<[u8; 32]> {
     match seed_type {
         SeedType::Bip39 => {
-            if words.len() != 12 && words.len() != 24 {
+            if indices.len() != 12 && indices.len() != 24 {
                 bail!(
                     "Invalid number of BIP39 seed words. Only 12 or 24 word phrases are supported."
                 );
             }
         }
         SeedType::Mobile => {
-            if words.len() != 12 {
+            if indices.len() != 12 {
                 bail!(
                     "Invalid number of mobile app seed words. Only 12 word phrases are supported."
                 );
@@ -46,17 +284,7 @@ pub fn mnemonic_to_entropy(words: Vec<String>, seed_type: &SeedType) -> Result<[
         }
     };
 
-    let wordlist = get_wordlist(Language::English);
-
-    let mut bit_vec = Vec::with_capacity(words.len());
-    for word in words.iter() {
-        let idx_bits = match wordlist.iter().position(|s| *s == word.to_lowercase()) {
-            Some(idx) => format!("{:011b}", idx),
-            _ => bail!("Seed word {} not found in wordlist", word),
-        };
-        bit_vec.push(idx_bits);
-    }
-    let bits = bit_vec.join("");
+    let bits: String = indices.iter().map(|idx| format!("{:011b}", idx)).collect();
 
     let divider_index: usize = ((bits.len() as f64 / 33.0) * 32.0).floor() as usize;
     let (entropy_bits, checksum_bits) = bits.split_at(divider_index);
@@ -73,7 +301,7 @@ pub fn mnemonic_to_entropy(words: Vec<String>, seed_type: &SeedType) -> Result<[
 
     let mut entropy_bytes = [0u8; 32];
     let valid_checksum;
-    if words.len() == 12 {
+    if indices.len() == 12 {
         let mut entropy_base = [0u8; 16];
         for (idx, matched) in RE_BYTES.find_iter(entropy_bits).enumerate() {
             entropy_base[idx] = binary_to_bytes(matched.as_str()) as u8;
@@ -106,6 +334,54 @@ pub fn mnemonic_to_entropy(words: Vec<String>, seed_type: &SeedType) -> Result<[
     Ok(entropy_bytes)
 }
 
+/// Converts 16 or 32 bytes of entropy to a 12 or 24 word BIP39 mnemonic in
+/// the given language
+pub fn entropy_to_mnemonic(entropy: &[u8], language: Language) -> Result<Vec<String>> {
+    let checksum_bits = match entropy.len() {
+        16 => {
+            let mut entropy_base = [0u8; 16];
+            entropy_base.copy_from_slice(entropy);
+            format!("{:04b}", calc_checksum_128(entropy_base))
+        }
+        32 => {
+            let mut entropy_base = [0u8; 32];
+            entropy_base.copy_from_slice(entropy);
+            format!("{:08b}", calc_checksum_256(entropy_base))
+        }
+        _ => bail!("Invalid entropy length. Only 16 or 32 bytes are supported."),
+    };
+
+    let entropy_bits: String = entropy.iter().map(|b| format!("{:08b}", b)).collect();
+    let bits = entropy_bits + &checksum_bits;
+
+    lazy_static! {
+        static ref RE_WORDS: Regex = Regex::new("(.{11})").unwrap();
+    }
+
+    let wordlist = get_wordlist(language);
+    Ok(RE_WORDS
+        .find_iter(&bits)
+        .map(|matched| wordlist[binary_to_bytes(matched.as_str())].to_string())
+        .collect())
+}
+
+/// Converts a BIP39 mnemonic and optional passphrase to the 64 byte seed
+/// used for hierarchical deterministic key derivation. Both the mnemonic and
+/// the passphrase are NFKD normalized.
+pub fn mnemonic_to_seed(words: &[String], passphrase: &str) -> Zeroizing<[u8; 64]> {
+    let mut seed = Zeroizing::new([0u8; 64]);
+    let mnemonic: Zeroizing<String> = Zeroizing::new(words.join(" ").nfkd().collect());
+    let salt: Zeroizing<String> =
+        Zeroizing::new(format!("mnemonic{}", passphrase).nfkd().collect());
+    pbkdf2::pbkdf2::<Hmac<Sha512>>(
+        mnemonic.as_bytes(),
+        salt.as_bytes(),
+        BIP39_SEED_ROUNDS,
+        &mut *seed,
+    );
+    seed
+}
+
 fn calc_checksum_128(bytes: [u8; 16]) -> u8 {
     // For 128-bit entropy, checksum is the first four bits of the sha256 hash
     (Sha256::digest(&bytes)[0] & 0b11110000) >> 4
@@ -116,6 +392,21 @@ fn calc_checksum_256(bytes: [u8; 32]) -> u8 {
     Sha256::digest(&bytes)[0]
 }
 
+/// Returns the Levenshtein edit distance between two words
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + if ca == cb { 0 } else { 1 };
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
 /// Converts a binary string into an integer
 fn binary_to_bytes(bin: &str) -> usize {
     usize::from_str_radix(bin, 2).unwrap() as usize
@@ -125,6 +416,182 @@ fn binary_to_bytes(bin: &str) -> usize {
 mod tests {
     use super::*;
 
+    #[test]
+    fn encode_bip39_12_words() {
+        // Same words and entropy as in decode_bip39_12_words
+        let words = "ritual ice harbor gas modify seed control solve burden people stay million";
+        let entropy = hex::decode("ba8e05a43008eb85cbde771e945b53c6").expect("entropy");
+        let word_list = entropy_to_mnemonic(&entropy, Language::English).expect("mnemonic");
+        assert_eq!(words, word_list.join(" "));
+    }
+
+    #[test]
+    fn encode_bip39_24_words() {
+        // Same words and entropy as in decode_bip39_24_words
+        let words = "pelican sphere tackle click broken hurt fork nephew choice seven announce moment tobacco tribe topple pause october drama sock erase news glove okay bubble";
+        let entropy =
+            hex::decode("a25a2f741551c8df96dca228389425477e33d0b94d0b99084738263952c76678")
+                .expect("entropy");
+        let word_list = entropy_to_mnemonic(&entropy, Language::English).expect("mnemonic");
+        assert_eq!(words, word_list.join(" "));
+        let decoded = mnemonic_to_entropy(word_list, &SeedType::Bip39, Some(Language::English))
+            .expect("entropy");
+        assert_eq!(entropy, decoded);
+    }
+
+    #[test]
+    fn bip39_reference_vectors() {
+        // Test vectors from the reference BIP39 implementation, which all use
+        // the passphrase "TREZOR"
+        const VECTORS: &[(&str, &str, &str)] = &[
+            (
+                "00000000000000000000000000000000",
+                "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+                "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+            ),
+            (
+                "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+                "legal winner thank year wave sausage worth useful legal winner thank yellow",
+                "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+            ),
+            (
+                "80808080808080808080808080808080",
+                "letter advice cage absurd amount doctor acoustic avoid letter advice cage above",
+                "d71de856f81a8acc65e6fc851a38d4d7ec216fd0796d0a6827a3ad6ed5511a30fa280f12eb2e47ed2ac03b5c462a0358d18d69fe4f985ec81778c1b370b652a8",
+            ),
+            (
+                "ffffffffffffffffffffffffffffffff",
+                "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong",
+                "ac27495480225222079d7be181583751e86f571027b0497b5b5d11218e0a8a13332572917f0f8e5a589620c6f15b11c61dee327651a14c34e18231052e48c069",
+            ),
+            (
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art",
+                "bda85446c68413707090a52022edd26a1c9462295029f2e60cd7c4f2bbd3097170af7a4d73245cafa9c3cca8d561a7c3de6f5d4a10be8ed2a5e608d68f92fcc8",
+            ),
+            (
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+                "dd48c104698c30cfe2b6142103248622fb7bb0ff692eebb00089b32d22484e1613912f0a5b694407be899ffd31ed3992c456cdf60f5d4564b8ba3f05a69890ad",
+            ),
+        ];
+        for (entropy, words, seed) in VECTORS {
+            let entropy = hex::decode(entropy).expect("entropy");
+            let word_list = entropy_to_mnemonic(&entropy, Language::English).expect("mnemonic");
+            assert_eq!(*words, word_list.join(" "));
+            mnemonic_to_entropy(word_list.clone(), &SeedType::Bip39, Some(Language::English))
+                .expect("valid checksum");
+            assert_eq!(
+                *seed,
+                hex::encode(&mnemonic_to_seed(&word_list, "TREZOR")[..])
+            );
+        }
+    }
+
+    #[test]
+    fn detect_languages() {
+        let entropy =
+            hex::decode("a25a2f741551c8df96dca228389425477e33d0b94d0b99084738263952c76678")
+                .expect("entropy");
+        for language in LANGUAGES {
+            let word_list = entropy_to_mnemonic(&entropy, *language).expect("mnemonic");
+            assert_eq!(
+                *language,
+                detect_language(&word_list, &SeedType::Bip39).expect("language")
+            );
+            let decoded = mnemonic_to_entropy(word_list, &SeedType::Bip39, None).expect("entropy");
+            assert_eq!(entropy, decoded);
+        }
+    }
+
+    #[test]
+    fn nfkd_word_lookup() {
+        // "ábaco" with a precomposed and with a combining accent
+        let composed = vec!["\u{e1}baco".to_string()];
+        let decomposed = vec!["a\u{301}baco".to_string()];
+        let wordlist = get_wordlist(Language::Spanish);
+        assert_eq!(Some(0), find_word(wordlist, &composed[0]));
+        assert_eq!(Some(0), find_word(wordlist, &decomposed[0]));
+        assert_eq!(
+            &mnemonic_to_seed(&composed, "")[..],
+            &mnemonic_to_seed(&decomposed, "")[..]
+        );
+    }
+
+    #[test]
+    fn bip39_japanese_vector() {
+        // Test vector from the reference Japanese BIP39 vectors, which use an
+        // ideographic space separator and a passphrase that changes under
+        // NFKD normalization
+        let words = "あいこくしん\u{3000}あいこくしん\u{3000}あいこくしん\u{3000}あいこくしん\u{3000}あいこくしん\u{3000}あいこくしん\u{3000}あいこくしん\u{3000}あいこくしん\u{3000}あいこくしん\u{3000}あいこくしん\u{3000}あいこくしん\u{3000}あおぞら";
+        let word_list: Vec<String> = words.split_whitespace().map(|w| w.to_string()).collect();
+        assert_eq!(
+            Language::Japanese,
+            detect_language(&word_list, &SeedType::Bip39).expect("language")
+        );
+        let seed = mnemonic_to_seed(&word_list, "㍍ガバヴァぱばぐゞちぢ十人十色");
+        assert_eq!("a262d6fb6122ecf45be09c50492b31f92e9beb7d9a845987a02cefda57a15f9c467a17872029a9e92299b5cbdf306e3a0ee620245cbd508959b6cb7ca637bd55", hex::encode(&seed[..]));
+    }
+
+    #[test]
+    fn bip39_passphrase_changes_seed() {
+        let words = "ritual ice harbor gas modify seed control solve burden people stay million";
+        let word_list: Vec<String> = words.split_whitespace().map(|w| w.to_string()).collect();
+        assert_ne!(
+            &mnemonic_to_seed(&word_list, "")[..],
+            &mnemonic_to_seed(&word_list, "passphrase")[..]
+        );
+    }
+
+    #[test]
+    fn word_prefixes() {
+        let words = "rit ice harb gas modi seed cont solv burd peop stay mill";
+        let word_list: Vec<String> = words.split_whitespace().map(|w| w.to_string()).collect();
+        // Three letter prefixes are not accepted
+        assert_eq!(vec![0], unknown_words(&word_list, Language::English));
+
+        let words = "ritu ice harb gas modi seed cont solv burd peop stay mill";
+        let word_list: Vec<String> = words.split_whitespace().map(|w| w.to_string()).collect();
+        assert_eq!(
+            "ritual ice harbor gas modify seed control solve burden people stay million",
+            expand_words(&word_list, Language::English)
+                .expect("expanded words")
+                .join(" ")
+        );
+    }
+
+    #[test]
+    fn word_suggestions() {
+        assert_eq!(
+            vec!["ritual", "rival", "vital"],
+            suggest_words("ritul", Language::English)
+        );
+        assert!(suggest_words("xxxxxxxx", Language::English).is_empty());
+        assert_eq!(
+            3,
+            edit_distance(
+                &['k', 'i', 't', 't', 'e', 'n'],
+                &['s', 'i', 't', 't', 'i', 'n', 'g']
+            )
+        );
+    }
+
+    #[test]
+    fn checksum_word_corrections() {
+        // "ice" replaced with "ivory" which breaks the checksum
+        let words = "ritual ivory harbor gas modify seed control solve burden people stay million";
+        let word_list: Vec<String> = words.split_whitespace().map(|w| w.to_string()).collect();
+        assert!(mnemonic_to_entropy(word_list.clone(), &SeedType::Bip39, None).is_err());
+        let corrections = checksum_corrections(&word_list, &SeedType::Bip39, Language::English);
+        assert!(corrections.contains(&(1, "ice")));
+        for (position, word) in corrections.into_iter().take(10) {
+            let mut corrected = word_list.clone();
+            corrected[position] = word.to_string();
+            mnemonic_to_entropy(corrected, &SeedType::Bip39, Some(Language::English))
+                .expect("valid checksum");
+        }
+    }
+
     #[test]
     fn decode_mobile_12_words() {
         // The words and entropy here were generated as follows: from the JS mobile-wallet implementation
@@ -134,7 +601,8 @@ mod tests {
             .expect("decoded entropy");
 
         let word_list = words.split_whitespace().map(|w| w.to_string()).collect();
-        let entropy = mnemonic_to_entropy(word_list, &SeedType::Mobile).expect("entropy");
+        let entropy = mnemonic_to_entropy(word_list, &SeedType::Mobile, Some(Language::English))
+            .expect("entropy");
         assert_eq!(expected_entropy, entropy);
     }
 
@@ -153,7 +621,8 @@ mod tests {
             .expect("decoded entropy");
 
         let word_list = words.split_whitespace().map(|w| w.to_string()).collect();
-        let entropy = mnemonic_to_entropy(word_list, &SeedType::Bip39).expect("entropy");
+        let entropy = mnemonic_to_entropy(word_list, &SeedType::Bip39, Some(Language::English))
+            .expect("entropy");
         assert_eq!(expected_entropy, entropy);
     }
 
@@ -171,7 +640,8 @@ mod tests {
             .expect("decoded entropy");
 
         let word_list = words.split_whitespace().map(|w| w.to_string()).collect();
-        let entropy = mnemonic_to_entropy(word_list, &SeedType::Bip39).expect("entropy");
+        let entropy = mnemonic_to_entropy(word_list, &SeedType::Bip39, Some(Language::English))
+            .expect("entropy");
         assert_eq!(expected_entropy, entropy);
     }
 }
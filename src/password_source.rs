@@ -0,0 +1,178 @@
+use crate::result::{bail, Result};
+use std::{
+    env, fmt, fs,
+    io::{self, Read},
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+use zeroize::Zeroizing;
+
+/// Environment variable holding the wallet password
+pub const PASSWORD_ENV: &str = "HELIUM_WALLET_PASSWORD";
+
+/// The longest password line read from a file, descriptor or command
+const MAX_PASSWORD_LENGTH: usize = 4096;
+
+/// Where the wallet password is read from. All sources except the prompt
+/// only use the first line of what they read, without the line ending.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum PasswordSource {
+    /// The `HELIUM_WALLET_PASSWORD` environment variable
+    Env,
+    /// A file, for example a secrets mount
+    File(PathBuf),
+    /// An open file descriptor inherited from the parent process
+    Fd(i32),
+    /// The standard output of a shell command
+    Command(String),
+    /// An interactive prompt on the terminal
+    #[default]
+    Prompt,
+}
+
+impl PasswordSource {
+    /// Reads the password. When prompting and `confirm` is set the password
+    /// has to be entered twice.
+    pub fn read(&self, confirm: bool) -> Result<Zeroizing<String>> {
+        match self {
+            Self::Env => match env::var(PASSWORD_ENV) {
+                Ok(password) => Ok(Zeroizing::new(password)),
+                Err(_) => bail!("{} is not set", PASSWORD_ENV),
+            },
+            Self::File(path) => read_line(fs::File::open(path)?),
+            Self::Fd(fd) => read_fd(*fd),
+            Self::Command(command) => {
+                let mut child = shell_command(command)
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::inherit())
+                    .spawn()?;
+                let mut stdout = child.stdout.take().expect("piped stdout");
+                // Read the password line straight from the pipe and drain
+                // the rest so the command is not stopped by a closed pipe
+                let password = read_line(&mut stdout);
+                let drained = io::copy(&mut stdout, &mut io::sink());
+                let status = child.wait()?;
+                if !status.success() {
+                    bail!("Password command failed: {}", status);
+                }
+                drained?;
+                password
+            }
+            Self::Prompt => {
+                let mut builder = dialoguer::Password::new();
+                builder.with_prompt("Password");
+                if confirm {
+                    builder.with_confirmation("Confirm password", "Passwords do not match");
+                };
+                Ok(builder.interact().map(Zeroizing::new)?)
+            }
+        }
+    }
+
+    /// Whether the password is read from standard input, which then can not
+    /// be used for anything else
+    pub fn reads_stdin(&self) -> bool {
+        *self == Self::Fd(0)
+    }
+}
+
+impl fmt::Display for PasswordSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Env => write!(f, "env {}", PASSWORD_ENV),
+            Self::File(path) => write!(f, "file {}", path.display()),
+            Self::Fd(fd) => write!(f, "fd {}", fd),
+            Self::Command(command) => write!(f, "command {}", command),
+            Self::Prompt => f.write_str("prompt"),
+        }
+    }
+}
+
+/// Reads the first line from the given reader. The reader is read a byte at
+/// a time so nothing past the line is consumed from a shared descriptor.
+/// The line buffer is allocated once so no copy of the password is left
+/// behind by growing it.
+fn read_line(mut reader: impl Read) -> Result<Zeroizing<String>> {
+    let mut line = Zeroizing::new(Vec::with_capacity(MAX_PASSWORD_LENGTH));
+    let mut byte = [0u8; 1];
+    loop {
+        match reader.read(&mut byte) {
+            Ok(0) => break,
+            Ok(_) if byte[0] == b'\n' => break,
+            Ok(_) if line.len() == MAX_PASSWORD_LENGTH => {
+                bail!("Password is longer than {} bytes", MAX_PASSWORD_LENGTH)
+            }
+            Ok(_) => line.push(byte[0]),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        }
+    }
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    match std::str::from_utf8(&line) {
+        Ok(password) => Ok(Zeroizing::new(password.to_string())),
+        Err(_) => bail!("Password is not valid UTF-8"),
+    }
+}
+
+#[cfg(unix)]
+fn read_fd(fd: i32) -> Result<Zeroizing<String>> {
+    use std::os::unix::io::FromRawFd;
+    // Read from a duplicate so the inherited descriptor itself stays open.
+    // The duplicate shares the file offset, so whatever follows the
+    // password line is left for the next reader.
+    let dup = unsafe { libc::dup(fd) };
+    if dup < 0 {
+        bail!(
+            "Invalid password file descriptor {}: {}",
+            fd,
+            io::Error::last_os_error()
+        );
+    }
+    read_line(unsafe { fs::File::from_raw_fd(dup) })
+}
+
+#[cfg(not(unix))]
+fn read_fd(_fd: i32) -> Result<Zeroizing<String>> {
+    bail!("Reading the password from a file descriptor is only supported on unix")
+}
+
+fn shell_command(command: &str) -> Command {
+    #[cfg(windows)]
+    {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    }
+    #[cfg(not(windows))]
+    {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_password() {
+        assert_eq!("secret", *read_line(&b"secret\r\nmore"[..]).expect("line"));
+        assert_eq!("secret", *read_line(&b"secret"[..]).expect("line"));
+        assert_eq!("", *read_line(&b""[..]).expect("line"));
+        #[cfg(unix)]
+        assert_eq!(
+            "secret",
+            *PasswordSource::Command("printf 'secret\\nrest'".to_string())
+                .read(false)
+                .expect("command")
+        );
+        assert!(PasswordSource::Command("exit 1".to_string())
+            .read(false)
+            .is_err());
+        assert!(PasswordSource::Fd(0).reads_stdin());
+    }
+}
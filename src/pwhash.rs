@@ -4,6 +4,10 @@ use hmac::Hmac;
 use sha2::Sha256;
 use sodiumoxide::{crypto::pwhash::argon2id13, randombytes};
 use std::{convert::TryInto, fmt, io};
+use zeroize::Zeroize;
+
+const PWHASH_KIND_PBKDF2: u8 = 0;
+const PWHASH_KIND_ARGON2ID13: u8 = 1;
 
 #[derive(Clone, Copy, Debug)]
 pub enum PwHash {
@@ -33,6 +37,26 @@ impl PwHash {
         }
     }
 
+    /// Reads the kind of a password hash and returns a hasher of that kind
+    /// with default parameters. The parameters themselves are read with
+    /// `read`.
+    pub fn read_kind(reader: &mut dyn io::Read) -> Result<Self> {
+        let kind = reader.read_u8()?;
+        match kind {
+            PWHASH_KIND_PBKDF2 => Ok(PwHash::pbkdf2_default()),
+            PWHASH_KIND_ARGON2ID13 => Ok(PwHash::argon2id13_default()),
+            _ => Err(anyhow!("Invalid pwhash kind {}", kind)),
+        }
+    }
+
+    pub fn write_kind(&self, writer: &mut dyn io::Write) -> Result {
+        match self {
+            PwHash::Pbkdf2(_) => writer.write_u8(PWHASH_KIND_PBKDF2)?,
+            PwHash::Argon2id13(_) => writer.write_u8(PWHASH_KIND_ARGON2ID13)?,
+        }
+        Ok(())
+    }
+
     pub fn pbkdf2_default() -> Self {
         PwHash::Pbkdf2(Pbkdf2::with_iterations(PBKDF2_DEFAULT_ITERATIONS))
     }
@@ -44,6 +68,24 @@ impl PwHash {
     pub fn argon2id13_default() -> Self {
         PwHash::Argon2id13(Argon2id13::default())
     }
+
+    pub fn argon2id13(ops_limit: usize, mem_limit: usize) -> Self {
+        PwHash::Argon2id13(Argon2id13::with_limits(
+            argon2id13::OpsLimit(ops_limit),
+            argon2id13::MemLimit(mem_limit),
+        ))
+    }
+
+    /// Returns a hasher with the same parameters as this one but with a
+    /// newly generated salt.
+    pub fn with_new_salt(&self) -> Self {
+        match self {
+            PwHash::Pbkdf2(hasher) => PwHash::Pbkdf2(Pbkdf2::with_iterations(hasher.iterations)),
+            PwHash::Argon2id13(hasher) => {
+                PwHash::Argon2id13(Argon2id13::with_limits(hasher.ops_limit, hasher.mem_limit))
+            }
+        }
+    }
 }
 
 impl fmt::Display for PwHash {
@@ -57,6 +99,17 @@ impl fmt::Display for PwHash {
 
 pub const PBKDF2_DEFAULT_ITERATIONS: u32 = 1_000_000;
 
+/// The minimum operations limit accepted by libsodium for Argon2id13
+pub const ARGON2ID13_OPSLIMIT_MIN: usize = 1;
+/// The minimum memory limit (in bytes) accepted by libsodium for Argon2id13
+pub const ARGON2ID13_MEMLIMIT_MIN: usize = 8192;
+/// The maximum operations limit. Limits are stored as 32 bit values in the
+/// wallet file, which is also the libsodium maximum.
+pub const ARGON2ID13_OPSLIMIT_MAX: usize = u32::MAX as usize;
+/// The maximum memory limit (in bytes). Limits are stored as 32 bit values
+/// in the wallet file, below the libsodium maximum.
+pub const ARGON2ID13_MEMLIMIT_MAX: usize = u32::MAX as usize;
+
 #[derive(Clone, Copy, Debug)]
 pub struct Pbkdf2 {
     salt: [u8; 8],
@@ -70,6 +123,10 @@ impl Pbkdf2 {
         Self { salt, iterations }
     }
 
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
     pub fn pwhash(&self, password: &[u8], hash: &mut [u8]) -> Result {
         pbkdf2::pbkdf2::<Hmac<Sha256>>(password, &self.salt, self.iterations, hash);
         Ok(())
@@ -113,10 +170,22 @@ impl Argon2id13 {
         }
     }
 
+    pub fn ops_limit(&self) -> usize {
+        self.ops_limit.0
+    }
+
+    pub fn mem_limit(&self) -> usize {
+        self.mem_limit.0
+    }
+
     pub fn pwhash(&self, password: &[u8], hash: &mut [u8]) -> Result {
         match argon2id13::derive_key(hash, password, &self.salt, self.ops_limit, self.mem_limit) {
             Ok(_) => Ok(()),
-            Err(_) => Err(anyhow!("Failed to hash password")),
+            Err(_) => {
+                // Don't leave a partially derived key behind
+                hash.zeroize();
+                Err(anyhow!("Failed to hash password"))
+            }
         }
     }
 
@@ -1,8 +1,10 @@
 use crate::{
     keypair::PublicKey,
+    memo::Memo,
     result::{anyhow, Result},
     traits::B64,
 };
+use helium_api::models::{Hnt, Hst, Usd};
 use helium_proto::*;
 use serde_json::json;
 
@@ -14,6 +16,10 @@ pub(crate) fn maybe_b58(data: &[u8]) -> Result<Option<String>> {
     }
 }
 
+pub(crate) fn b58(data: &[u8]) -> Result<String> {
+    Ok(PublicKey::from_bytes(data)?.to_string())
+}
+
 pub(crate) fn maybe_b64_url(data: &[u8]) -> Result<Option<String>> {
     if data.is_empty() {
         Ok(None)
@@ -115,12 +121,358 @@ impl ToJson for BlockchainTxnTransferHotspotV1 {
         let buyer = PublicKey::from_bytes(&self.buyer)?.to_string();
 
         Ok(json!({
+            "type": "transfer_hotspot_v1",
             "seller": seller,
             "gateway": gateway,
             "buyer": buyer,
-            "amount_to_seller": self.amount_to_seller,
+            "amount_to_seller": Hnt::from(self.amount_to_seller),
             "buyer_nonce": self.buyer_nonce,
-            "fee": self.fee
+            "fee": self.fee,
+            "seller_signature": maybe_b64_url(&self.seller_signature)?,
+            "buyer_signature": maybe_b64_url(&self.buyer_signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnPriceOracleV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "price_oracle_v1",
+            "public_key": b58(&self.public_key)?,
+            "price": Usd::from(self.price),
+            "block_height": self.block_height,
+            "signature": maybe_b64_url(&self.signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnOuiV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "oui_v1",
+            "owner": b58(&self.owner)?,
+            "payer": maybe_b58(&self.payer)?,
+            "oui": self.oui,
+            "addresses": vec_to_b58s(&self.addresses)?,
+            "filter": maybe_b64_url(&self.filter)?,
+            "requested_subnet_size": self.requested_subnet_size,
+            "staking_fee": self.staking_fee,
+            "fee": self.fee,
+            "owner_signature": maybe_b64_url(&self.owner_signature)?,
+            "payer_signature": maybe_b64_url(&self.payer_signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnCreateHtlcV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "create_htlc_v1",
+            "payer": b58(&self.payer)?,
+            "payee": b58(&self.payee)?,
+            "address": b58(&self.address)?,
+            "hashlock": hex::encode(&self.hashlock),
+            "timelock": self.timelock,
+            "amount": Hnt::from(self.amount),
+            "nonce": self.nonce,
+            "fee": self.fee,
+            "signature": maybe_b64_url(&self.signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnRedeemHtlcV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "redeem_htlc_v1",
+            "payee": b58(&self.payee)?,
+            "address": b58(&self.address)?,
+            "preimage": String::from_utf8_lossy(&self.preimage),
+            "fee": self.fee,
+            "signature": maybe_b64_url(&self.signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnPaymentV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "payment_v1",
+            "payer": b58(&self.payer)?,
+            "payee": b58(&self.payee)?,
+            "amount": Hnt::from(self.amount),
+            "nonce": self.nonce,
+            "fee": self.fee,
+            "signature": maybe_b64_url(&self.signature)?,
+        }))
+    }
+}
+
+impl ToJson for Payment {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "payee": b58(&self.payee)?,
+            "amount": Hnt::from(self.amount),
+            "memo": Memo::from(self.memo).to_string(),
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnPaymentV2 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "payment_v2",
+            "payer": b58(&self.payer)?,
+            "payments": self.payments.to_json()?,
+            "nonce": self.nonce,
+            "fee": self.fee,
+            "signature": maybe_b64_url(&self.signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnSecurityExchangeV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "security_exchange_v1",
+            "payer": b58(&self.payer)?,
+            "payee": b58(&self.payee)?,
+            "amount": Hst::from(self.amount),
+            "nonce": self.nonce,
+            "fee": self.fee,
+            "signature": maybe_b64_url(&self.signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnTokenBurnV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "token_burn_v1",
+            "payer": b58(&self.payer)?,
+            "payee": b58(&self.payee)?,
+            "amount": Hnt::from(self.amount),
+            "memo": Memo::from(self.memo).to_string(),
+            "nonce": self.nonce,
+            "fee": self.fee,
+            "signature": maybe_b64_url(&self.signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnAddGatewayV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "add_gateway_v1",
+            "gateway": b58(&self.gateway)?,
+            "owner": b58(&self.owner)?,
+            "payer": maybe_b58(&self.payer)?,
+            "staking_fee": self.staking_fee,
+            "fee": self.fee,
+            "gateway_signature": maybe_b64_url(&self.gateway_signature)?,
+            "owner_signature": maybe_b64_url(&self.owner_signature)?,
+            "payer_signature": maybe_b64_url(&self.payer_signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnAssertLocationV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "assert_location_v1",
+            "gateway": b58(&self.gateway)?,
+            "owner": b58(&self.owner)?,
+            "payer": maybe_b58(&self.payer)?,
+            "location": self.location,
+            "nonce": self.nonce,
+            "staking_fee": self.staking_fee,
+            "fee": self.fee,
+            "gateway_signature": maybe_b64_url(&self.gateway_signature)?,
+            "owner_signature": maybe_b64_url(&self.owner_signature)?,
+            "payer_signature": maybe_b64_url(&self.payer_signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnAssertLocationV2 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "assert_location_v2",
+            "gateway": b58(&self.gateway)?,
+            "owner": b58(&self.owner)?,
+            "payer": maybe_b58(&self.payer)?,
+            "location": self.location,
+            "elevation": self.elevation,
+            "gain": self.gain,
+            "nonce": self.nonce,
+            "staking_fee": self.staking_fee,
+            "fee": self.fee,
+            "owner_signature": maybe_b64_url(&self.owner_signature)?,
+            "payer_signature": maybe_b64_url(&self.payer_signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnStakeValidatorV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "stake_validator_v1",
+            "address": b58(&self.address)?,
+            "owner": b58(&self.owner)?,
+            "stake": Hnt::from(self.stake),
+            "fee": self.fee,
+            "owner_signature": maybe_b64_url(&self.owner_signature)?,
         }))
     }
 }
+
+impl ToJson for BlockchainTxnUnstakeValidatorV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "unstake_validator_v1",
+            "address": b58(&self.address)?,
+            "owner": b58(&self.owner)?,
+            "stake_amount": Hnt::from(self.stake_amount),
+            "stake_release_height": self.stake_release_height,
+            "fee": self.fee,
+            "owner_signature": maybe_b64_url(&self.owner_signature)?,
+        }))
+    }
+}
+
+impl ToJson for BlockchainTxnTransferValidatorStakeV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(json!({
+            "type": "transfer_validator_stake_v1",
+            "old_address": b58(&self.old_address)?,
+            "new_address": b58(&self.new_address)?,
+            "old_owner": b58(&self.old_owner)?,
+            "new_owner": maybe_b58(&self.new_owner)?,
+            "stake_amount": Hnt::from(self.stake_amount),
+            "payment_amount": Hnt::from(self.payment_amount),
+            "fee": self.fee,
+            "old_owner_signature": maybe_b64_url(&self.old_owner_signature)?,
+            "new_owner_signature": maybe_b64_url(&self.new_owner_signature)?,
+        }))
+    }
+}
+
+impl ToJson for blockchain_txn_routing_v1::Update {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        use blockchain_txn_routing_v1::Update;
+        let map = match self {
+            Update::UpdateRouters(update) => json!({
+                "action": "update_routers",
+                "router_addresses": vec_to_b58s(&update.router_addresses)?,
+            }),
+            Update::NewXor(filter) => json!({
+                "action": "new_xor",
+                "filter": maybe_b64_url(filter)?,
+            }),
+            Update::UpdateXor(update) => json!({
+                "action": "update_xor",
+                "index": update.index,
+                "filter": maybe_b64_url(&update.filter)?,
+            }),
+            Update::RequestSubnet(size) => json!({
+                "action": "request_subnet",
+                "subnet_size": size,
+            }),
+        };
+        Ok(map)
+    }
+}
+
+impl ToJson for BlockchainTxnRoutingV1 {
+    fn to_json(&self) -> Result<serde_json::Value> {
+        let update = match &self.update {
+            Some(update) => update.to_json()?,
+            None => json!(null),
+        };
+        Ok(json!({
+            "type": "routing_v1",
+            "oui": self.oui,
+            "owner": b58(&self.owner)?,
+            "update": update,
+            "nonce": self.nonce,
+            "staking_fee": self.staking_fee,
+            "fee": self.fee,
+            "signature": maybe_b64_url(&self.signature)?,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::Keypair;
+
+    #[test]
+    fn payment_v2_json() {
+        let payer = Keypair::default();
+        let payee = Keypair::default();
+        let txn = BlockchainTxnPaymentV2 {
+            payer: payer.public_key().to_vec(),
+            payments: vec![Payment {
+                payee: payee.public_key().to_vec(),
+                amount: 150_000_000,
+                memo: 1,
+            }],
+            nonce: 7,
+            fee: 35_000,
+            signature: vec![],
+        };
+        let json = txn.to_json().expect("json");
+        assert_eq!("payment_v2", json["type"]);
+        assert_eq!(json!(payer.public_key().to_string()), json["payer"]);
+        let payment = &json["payments"][0];
+        assert_eq!(json!(payee.public_key().to_string()), payment["payee"]);
+        assert_eq!(json!(Hnt::from(150_000_000)), payment["amount"]);
+        assert_ne!(json!(150_000_000), payment["amount"]);
+        assert_eq!("AQAAAAAAAAA=", payment["memo"]);
+        // Fees are in DC and shown as is
+        assert_eq!(json!(35_000), json["fee"]);
+        assert_eq!(json!(null), json["signature"]);
+    }
+
+    #[test]
+    fn token_burn_v1_json() {
+        let payer = Keypair::default();
+        let payee = Keypair::default();
+        let txn = BlockchainTxnTokenBurnV1 {
+            payer: payer.public_key().to_vec(),
+            payee: payee.public_key().to_vec(),
+            amount: 100_000_000,
+            memo: u64::MAX,
+            nonce: 1,
+            fee: 0,
+            signature: vec![1, 2, 3],
+        };
+        let json = txn.to_json().expect("json");
+        assert_eq!(json!(payer.public_key().to_string()), json["payer"]);
+        assert_eq!(json!(payee.public_key().to_string()), json["payee"]);
+        assert_eq!(json!(Hnt::from(100_000_000)), json["amount"]);
+        assert_eq!("//////////8=", json["memo"]);
+        assert_eq!("AQID", json["signature"]);
+    }
+
+    #[test]
+    fn security_exchange_v1_json() {
+        let txn = BlockchainTxnSecurityExchangeV1 {
+            payer: Keypair::default().public_key().to_vec(),
+            payee: Keypair::default().public_key().to_vec(),
+            amount: 250_000_000,
+            nonce: 1,
+            fee: 0,
+            signature: vec![],
+        };
+        let json = txn.to_json().expect("json");
+        assert_eq!(json!(Hst::from(250_000_000)), json["amount"]);
+    }
+
+    #[test]
+    fn invalid_address() {
+        assert!(b58(&[0u8; 3]).is_err());
+        assert_eq!(None, maybe_b58(&[]).expect("empty"));
+    }
+}
@@ -4,7 +4,8 @@ pub use self::read_write::ReadWrite;
 pub use self::txn_envelope::TxnEnvelope;
 pub use self::txn_fee::{TxnFee, TxnFeeConfig, TxnModeStakingFee, TxnStakingFee};
 pub use self::txn_payer::TxnPayer;
-pub use self::txn_sign::TxnSign;
+pub use self::txn_sign::{TxnSign, TxnSigner};
+pub use self::txn_signatures::{SignatureStatus, TxnSignature, TxnSignatures};
 
 pub mod b64;
 pub mod json;
@@ -13,3 +14,4 @@ pub mod txn_envelope;
 pub mod txn_fee;
 pub mod txn_payer;
 pub mod txn_sign;
+pub mod txn_signatures;
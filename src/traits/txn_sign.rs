@@ -1,31 +1,56 @@
 use crate::keypair::{Keypair, PublicKey, Verify};
-use crate::result::Result;
+use crate::result::{bail, Result};
+use crate::traits::TxnEnvelope;
 use helium_proto::*;
+use sha2::{Digest, Sha256};
 
 pub trait TxnSign: Message + std::clone::Clone {
-    fn sign(&self, keypair: &Keypair) -> Result<Vec<u8>>
+    fn sign(&self, signer: &dyn TxnSigner) -> Result<Vec<u8>>
     where
         Self: std::marker::Sized;
     fn verify(&self, pubkey: &PublicKey, signature: &[u8]) -> Result;
+    /// The transaction hash as computed by the blockchain: the sha256 of
+    /// the encoded transaction with all signatures cleared.
+    fn hash(&self) -> Result<Vec<u8>>;
+    /// The message that is signed: the encoded transaction with all
+    /// signatures cleared.
+    fn message(&self) -> Result<Vec<u8>>;
+}
+
+/// Signs the encoded message of a transaction. The transaction is passed in
+/// its envelope as well, so a signer that does not trust its caller can
+/// compute the message from it with `signing_message`.
+pub trait TxnSigner {
+    fn sign_txn(&self, envelope: &BlockchainTxn, msg: &[u8]) -> Result<Vec<u8>>;
+}
+
+impl TxnSigner for Keypair {
+    fn sign_txn(&self, _envelope: &BlockchainTxn, msg: &[u8]) -> Result<Vec<u8>> {
+        self.sign(msg)
+    }
 }
 
 macro_rules! impl_sign {
     ($txn_type:ty, $( $sig: ident ),+ ) => {
         impl TxnSign for $txn_type {
-            fn sign(&self, keypair: &Keypair) -> Result<Vec<u8>> {
-                let mut buf = vec![];
-                let mut txn = self.clone();
-                $(txn.$sig = vec![];)+
-                txn.encode(& mut buf)?;
-                keypair.sign(&buf)
+            fn sign(&self, signer: &dyn TxnSigner) -> Result<Vec<u8>> {
+                signer.sign_txn(&self.in_envelope(), &self.message()?)
             }
 
             fn verify(&self, pubkey: &PublicKey, signature: &[u8]) -> Result {
+                pubkey.verify(&self.message()?, &signature).map_err(|err| err.into())
+            }
+
+            fn hash(&self) -> Result<Vec<u8>> {
+                Ok(Sha256::digest(&self.message()?).to_vec())
+            }
+
+            fn message(&self) -> Result<Vec<u8>> {
                 let mut buf = vec![];
                 let mut txn = self.clone();
                 $(txn.$sig = vec![];)+
                 txn.encode(& mut buf)?;
-                pubkey.verify(&buf, &signature).map_err(|err| err.into())
+                Ok(buf)
             }
         }
     }
@@ -76,3 +101,79 @@ impl_sign!(
     new_owner_signature
 );
 impl_sign!(BlockchainTxnRoutingV1, signature);
+
+macro_rules! describe_txns {
+    ($( $txn_type:ident ),+ ) => {
+        /// Decodes the message of a transaction of the given kind, as passed
+        /// to `TxnSigner::sign_txn`, into a readable description. Fails for
+        /// kinds that can not be signed.
+        pub fn describe_txn(kind: &str, msg: &[u8]) -> Result<String> {
+            $(
+                if kind == stringify!($txn_type).trim_start_matches("BlockchainTxn") {
+                    return Ok(format!("{:#?}", $txn_type::decode(msg)?));
+                }
+            )+
+            bail!("Unsupported transaction kind {}", kind)
+        }
+    }
+}
+
+describe_txns!(
+    BlockchainTxnPriceOracleV1,
+    BlockchainTxnPaymentV1,
+    BlockchainTxnPaymentV2,
+    BlockchainTxnCreateHtlcV1,
+    BlockchainTxnRedeemHtlcV1,
+    BlockchainTxnAddGatewayV1,
+    BlockchainTxnAssertLocationV1,
+    BlockchainTxnAssertLocationV2,
+    BlockchainTxnOuiV1,
+    BlockchainTxnSecurityExchangeV1,
+    BlockchainTxnTokenBurnV1,
+    BlockchainTxnVarsV1,
+    BlockchainTxnTransferHotspotV1,
+    BlockchainTxnStakeValidatorV1,
+    BlockchainTxnUnstakeValidatorV1,
+    BlockchainTxnTransferValidatorStakeV1,
+    BlockchainTxnRoutingV1
+);
+
+macro_rules! signing_messages {
+    ($( ($kind:ident, $txn_type:ident) ),+ ) => {
+        /// Returns the kind and the message to sign of the transaction in
+        /// the given envelope. The kind is the name of the transaction type
+        /// without the `BlockchainTxn` prefix, like `PaymentV2`. Fails for
+        /// transactions that can not be signed.
+        pub fn signing_message(envelope: &BlockchainTxn) -> Result<(&'static str, Vec<u8>)> {
+            match &envelope.txn {
+                $(
+                    Some(Txn::$kind(txn)) => Ok((
+                        stringify!($txn_type).trim_start_matches("BlockchainTxn"),
+                        txn.message()?,
+                    )),
+                )+
+                _ => bail!("Unsupported transaction"),
+            }
+        }
+    }
+}
+
+signing_messages!(
+    (PriceOracleSubmission, BlockchainTxnPriceOracleV1),
+    (Payment, BlockchainTxnPaymentV1),
+    (PaymentV2, BlockchainTxnPaymentV2),
+    (CreateHtlc, BlockchainTxnCreateHtlcV1),
+    (RedeemHtlc, BlockchainTxnRedeemHtlcV1),
+    (AddGateway, BlockchainTxnAddGatewayV1),
+    (AssertLocation, BlockchainTxnAssertLocationV1),
+    (AssertLocationV2, BlockchainTxnAssertLocationV2),
+    (Oui, BlockchainTxnOuiV1),
+    (SecurityExchange, BlockchainTxnSecurityExchangeV1),
+    (TokenBurn, BlockchainTxnTokenBurnV1),
+    (Vars, BlockchainTxnVarsV1),
+    (TransferHotspot, BlockchainTxnTransferHotspotV1),
+    (StakeValidator, BlockchainTxnStakeValidatorV1),
+    (UnstakeValidator, BlockchainTxnUnstakeValidatorV1),
+    (TransferValStake, BlockchainTxnTransferValidatorStakeV1),
+    (Routing, BlockchainTxnRoutingV1)
+);
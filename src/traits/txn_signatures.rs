@@ -0,0 +1,265 @@
+use super::{TxnSign, TxnSigner};
+use crate::{keypair::PublicKey, result::Result};
+use helium_proto::*;
+use std::fmt;
+
+/// State of a signature field in a transaction
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SignatureStatus {
+    /// Signed by the expected signer
+    Valid,
+    /// Signed, but not by the expected signer or for different content
+    Invalid,
+    /// Signed by a key that is not part of the transaction
+    Present,
+    /// Not signed yet
+    Missing,
+    /// Not signed and not needed, like the payer signature of a transaction
+    /// without a separate payer
+    NotRequired,
+}
+
+impl fmt::Display for SignatureStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Valid => f.write_str("valid"),
+            Self::Invalid => f.write_str("invalid"),
+            Self::Present => f.write_str("present"),
+            Self::Missing => f.write_str("missing"),
+            Self::NotRequired => f.write_str("not required"),
+        }
+    }
+}
+
+/// A signature field of a transaction and the key expected to sign it
+#[derive(Clone, Debug)]
+pub struct TxnSignature {
+    pub field: &'static str,
+    pub signer: Option<PublicKey>,
+    pub status: SignatureStatus,
+}
+
+impl TxnSignature {
+    /// Checks a signature against the signer named in the transaction. An
+    /// empty signer means the signature is optional.
+    fn check<T: TxnSign>(
+        txn: &T,
+        field: &'static str,
+        signer: &[u8],
+        signature: &[u8],
+    ) -> Result<Self> {
+        let signer = if signer.is_empty() {
+            None
+        } else {
+            Some(PublicKey::from_bytes(signer)?)
+        };
+        let status = match &signer {
+            None if signature.is_empty() => SignatureStatus::NotRequired,
+            None => SignatureStatus::Present,
+            Some(_) if signature.is_empty() => SignatureStatus::Missing,
+            Some(signer) if txn.verify(signer, signature).is_ok() => SignatureStatus::Valid,
+            Some(_) => SignatureStatus::Invalid,
+        };
+        Ok(Self {
+            field,
+            signer,
+            status,
+        })
+    }
+}
+
+pub trait TxnSignatures {
+    /// Lists the signature fields of the transaction with their signer and
+    /// whether they are signed.
+    fn signatures(&self) -> Result<Vec<TxnSignature>>;
+
+    /// Signs every signature field whose signer is the given key and
+    /// returns the names of the signed fields.
+    fn sign_for(&mut self, key: &PublicKey, signer: &dyn TxnSigner) -> Result<Vec<&'static str>>;
+}
+
+macro_rules! impl_txn_signatures {
+    ($txn_type:ty, $( ($sig:ident, $signer:ident) ),+ ) => {
+        impl TxnSignatures for $txn_type {
+            fn signatures(&self) -> Result<Vec<TxnSignature>> {
+                Ok(vec![
+                    $(TxnSignature::check(self, stringify!($sig), &self.$signer, &self.$sig)?),+
+                ])
+            }
+
+            fn sign_for(
+                &mut self,
+                key: &PublicKey,
+                signer: &dyn TxnSigner,
+            ) -> Result<Vec<&'static str>> {
+                let key = key.to_vec();
+                let mut signed = vec![];
+                $(
+                    if self.$signer == key {
+                        self.$sig = self.sign(signer)?;
+                        signed.push(stringify!($sig));
+                    }
+                )+
+                Ok(signed)
+            }
+        }
+    }
+}
+
+impl_txn_signatures!(BlockchainTxnPriceOracleV1, (signature, public_key));
+impl_txn_signatures!(BlockchainTxnPaymentV1, (signature, payer));
+impl_txn_signatures!(BlockchainTxnPaymentV2, (signature, payer));
+impl_txn_signatures!(BlockchainTxnCreateHtlcV1, (signature, payer));
+impl_txn_signatures!(BlockchainTxnRedeemHtlcV1, (signature, payee));
+impl_txn_signatures!(
+    BlockchainTxnAddGatewayV1,
+    (owner_signature, owner),
+    (payer_signature, payer),
+    (gateway_signature, gateway)
+);
+impl_txn_signatures!(
+    BlockchainTxnAssertLocationV1,
+    (owner_signature, owner),
+    (payer_signature, payer),
+    (gateway_signature, gateway)
+);
+impl_txn_signatures!(
+    BlockchainTxnAssertLocationV2,
+    (owner_signature, owner),
+    (payer_signature, payer)
+);
+impl_txn_signatures!(
+    BlockchainTxnOuiV1,
+    (owner_signature, owner),
+    (payer_signature, payer)
+);
+impl_txn_signatures!(BlockchainTxnSecurityExchangeV1, (signature, payer));
+impl_txn_signatures!(BlockchainTxnTokenBurnV1, (signature, payer));
+impl_txn_signatures!(
+    BlockchainTxnTransferHotspotV1,
+    (seller_signature, seller),
+    (buyer_signature, buyer)
+);
+impl_txn_signatures!(BlockchainTxnStakeValidatorV1, (owner_signature, owner));
+impl_txn_signatures!(BlockchainTxnUnstakeValidatorV1, (owner_signature, owner));
+impl_txn_signatures!(
+    BlockchainTxnTransferValidatorStakeV1,
+    (old_owner_signature, old_owner),
+    (new_owner_signature, new_owner)
+);
+impl_txn_signatures!(BlockchainTxnRoutingV1, (signature, owner));
+
+// Chain variables are signed by the master key or a quorum of multi keys
+// held by the chain, which are not part of the transaction, so the proofs
+// can only be reported as present or missing.
+impl TxnSignatures for BlockchainTxnVarsV1 {
+    fn signatures(&self) -> Result<Vec<TxnSignature>> {
+        let status = |signed: bool, required: bool| match (signed, required) {
+            (true, _) => SignatureStatus::Present,
+            (false, true) => SignatureStatus::Missing,
+            (false, false) => SignatureStatus::NotRequired,
+        };
+        let multi_signed = !self.multi_proofs.is_empty();
+        let mut signatures = vec![
+            TxnSignature {
+                field: "proof",
+                signer: None,
+                status: status(!self.proof.is_empty(), !multi_signed),
+            },
+            TxnSignature {
+                field: "key_proof",
+                signer: None,
+                status: status(!self.key_proof.is_empty(), !self.master_key.is_empty()),
+            },
+        ];
+        for _ in &self.multi_proofs {
+            signatures.push(TxnSignature {
+                field: "multi_proofs",
+                signer: None,
+                status: SignatureStatus::Present,
+            });
+        }
+        for _ in &self.multi_key_proofs {
+            signatures.push(TxnSignature {
+                field: "multi_key_proofs",
+                signer: None,
+                status: SignatureStatus::Present,
+            });
+        }
+        Ok(signatures)
+    }
+
+    fn sign_for(&mut self, _key: &PublicKey, _signer: &dyn TxnSigner) -> Result<Vec<&'static str>> {
+        // Chain variable proofs are collected with the multisig commands
+        Ok(vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::Keypair;
+
+    fn statuses(txn: &impl TxnSignatures) -> Vec<SignatureStatus> {
+        txn.signatures()
+            .unwrap()
+            .iter()
+            .map(|signature| signature.status)
+            .collect()
+    }
+
+    #[test]
+    fn payment_v1_signatures() {
+        let payer = Keypair::default();
+        let payee = Keypair::default();
+        let mut txn = BlockchainTxnPaymentV1 {
+            payee: payee.public_key().to_vec(),
+            payer: payer.public_key().to_vec(),
+            amount: 10_000,
+            nonce: 1,
+            fee: 0,
+            signature: vec![],
+        };
+        let hash = txn.hash().unwrap();
+        assert_eq!(statuses(&txn), vec![SignatureStatus::Missing]);
+
+        txn.signature = txn.sign(&payer).unwrap();
+        assert_eq!(statuses(&txn), vec![SignatureStatus::Valid]);
+        assert_eq!(txn.hash().unwrap(), hash);
+
+        txn.signature = txn.sign(&payee).unwrap();
+        assert_eq!(statuses(&txn), vec![SignatureStatus::Invalid]);
+    }
+
+    #[test]
+    fn add_gateway_signatures() {
+        let owner = Keypair::default();
+        let gateway = Keypair::default();
+        let mut txn = BlockchainTxnAddGatewayV1 {
+            owner: owner.public_key().to_vec(),
+            gateway: gateway.public_key().to_vec(),
+            payer: vec![],
+            staking_fee: 0,
+            fee: 0,
+            owner_signature: vec![],
+            gateway_signature: vec![],
+            payer_signature: vec![],
+        };
+        assert_eq!(
+            txn.sign_for(gateway.public_key(), &gateway).unwrap(),
+            vec!["gateway_signature"]
+        );
+        assert!(txn
+            .sign_for(Keypair::default().public_key(), &owner)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            statuses(&txn),
+            vec![
+                SignatureStatus::Missing,
+                SignatureStatus::NotRequired,
+                SignatureStatus::Valid
+            ]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
-    format::{self, Format},
+    format::{self, Format, Keyfile},
     keypair::{Keypair, PublicKey},
+    metadata::Metadata,
     pwhash::PwHash,
     result::{anyhow, bail, Result},
     traits::ReadWrite,
@@ -11,20 +12,54 @@ use aes_gcm::{
 };
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use sodiumoxide::randombytes;
-use std::io::{self, Cursor};
+use std::{
+    fmt,
+    io::{self, Cursor},
+};
+use zeroize::Zeroizing;
 
 pub type Tag = [u8; 16];
 pub type Iv = [u8; 12];
+/// The AES encryption key of a wallet. Keys are wrapped in `Zeroizing` while
+/// in use so they are cleared from memory when dropped.
 pub type AesKey = [u8; 32];
 
 const WALLET_KIND_BASIC_V1: u16 = 0x0001;
 const WALLET_KIND_BASIC_V2: u16 = 0x0002;
+const WALLET_KIND_BASIC_V3: u16 = 0x0003;
 
 const WALLET_KIND_SHARDED_V1: u16 = 0x0101;
 const WALLET_KIND_SHARDED_V2: u16 = 0x0102;
+const WALLET_KIND_SHARDED_V3: u16 = 0x0103;
+
+// Key slot wallets follow the versions of the other wallet kinds, V3 being
+// the one with metadata
+const WALLET_KIND_SLOTS_V2: u16 = 0x0202;
+const WALLET_KIND_SLOTS_V3: u16 = 0x0203;
+
+/// Flag set in the wallet kind of wallets that need a keyfile to decrypt
+const WALLET_KIND_KEYFILE: u16 = 0x1000;
 
-const PWHASH_KIND_PBKDF2: u8 = 0;
-const PWHASH_KIND_ARGON2ID13: u8 = 1;
+/// The result of checking a single key share of a sharded wallet
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShardStatus {
+    /// The key share helps decrypt the wallet
+    Good,
+    /// The key share does not belong to the key shares that decrypt the wallet
+    Corrupt,
+    /// The wallet could not be decrypted with the given key shares
+    Unknown,
+}
+
+impl fmt::Display for ShardStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShardStatus::Good => f.write_str("ok"),
+            ShardStatus::Corrupt => f.write_str("corrupt"),
+            ShardStatus::Unknown => f.write_str("unknown"),
+        }
+    }
+}
 
 pub struct Wallet {
     pub public_key: PublicKey,
@@ -32,59 +67,189 @@ pub struct Wallet {
     pub tag: Tag,
     pub encrypted: Vec<u8>,
     pub format: Format,
+    /// Optional metadata, only stored by V3 wallets
+    pub metadata: Option<Metadata>,
+    /// Whether a keyfile is needed in addition to the password
+    pub keyfile: bool,
 }
 
 impl Wallet {
-    pub fn encrypt(keypair: &Keypair, password: &[u8], fmt: Format) -> Result<Wallet> {
-        let mut encryption_key = AesKey::default();
+    pub fn encrypt(
+        keypair: &Keypair,
+        password: &[u8],
+        keyfile: Option<&Keyfile>,
+        fmt: Format,
+        metadata: Option<Metadata>,
+    ) -> Result<Wallet> {
+        let mut encryption_key = Zeroizing::new(AesKey::default());
         let mut format = fmt;
         let public_key = keypair.public_key();
-        format.derive_key(password, &mut encryption_key)?;
+        format.derive_key(password, keyfile, &mut *encryption_key)?;
 
         let mut iv = Iv::default();
         randombytes::randombytes_into(&mut iv);
 
-        let aead = Aes256Gcm::new(GenericArray::from_slice(&encryption_key));
+        let aead = Aes256Gcm::new(GenericArray::from_slice(&*encryption_key));
 
-        let mut encrypted = vec![];
-        keypair.write(&mut encrypted)?;
+        // The buffer holds the unencrypted keypair until encrypted in place.
+        // It is allocated at its final size so growing it does not leave
+        // copies of the private key behind.
+        let mut encrypted = Zeroizing::new(Vec::with_capacity(keypair.encoded_len()));
+        keypair.write(&mut *encrypted)?;
 
-        match aead.encrypt_in_place_detached(
-            iv.as_ref().into(),
-            &public_key.to_bytes(),
-            &mut encrypted,
-        ) {
+        let associated_data = Self::associated_data(public_key, &metadata)?;
+        match aead.encrypt_in_place_detached(iv.as_ref().into(), &associated_data, &mut encrypted) {
             Err(_) => Err(anyhow!("Failed to encrypt wallet")),
             Ok(gtag) => Ok(Wallet {
                 public_key: public_key.clone(),
                 iv,
                 tag: gtag.into(),
-                encrypted,
+                encrypted: encrypted.to_vec(),
                 format,
+                metadata,
+                keyfile: keyfile.is_some(),
             }),
         }
     }
 
-    pub fn decrypt(&self, password: &[u8]) -> Result<Keypair> {
-        let mut encryption_key = AesKey::default();
+    pub fn decrypt(&self, password: &[u8], keyfile: Option<&Keyfile>) -> Result<Keypair> {
+        if self.is_sharded() {
+            return match self.decrypt_key_shares(password, keyfile)? {
+                Some((keypair, _)) => Ok(keypair),
+                None => Err(anyhow!("Failed to decrypt wallet")),
+            };
+        }
+        self.check_keyfile(keyfile)?;
+        let mut encryption_key = Zeroizing::new(AesKey::default());
         let mut format = self.format.clone();
-        format.derive_key(password, &mut encryption_key)?;
+        format.derive_key(password, keyfile, &mut *encryption_key)?;
+        self.decrypt_with_key(&encryption_key)
+    }
 
-        let aead = Aes256Gcm::new(GenericArray::from_slice(&encryption_key));
-        let mut buffer = self.encrypted.to_owned();
+    /// Decrypts a sharded wallet. Returns the decrypted keypair and the
+    /// indices of the key shares that decrypted it, or None if no set of key
+    /// shares decrypts the wallet.
+    pub fn decrypt_key_shares(
+        &self,
+        password: &[u8],
+        keyfile: Option<&Keyfile>,
+    ) -> Result<Option<(Keypair, Vec<usize>)>> {
+        self.check_keyfile(keyfile)?;
+        let format = self.sharded_format()?;
+        let mut stretched_key = Zeroizing::new(AesKey::default());
+        format::stretch_key(&format.pwhash, password, keyfile, &mut *stretched_key)?;
+        self.find_key_shares(format, &stretched_key)
+    }
+
+    fn check_keyfile(&self, keyfile: Option<&Keyfile>) -> Result {
+        match (self.keyfile, keyfile) {
+            (true, None) => bail!("Wallet requires a keyfile"),
+            (false, Some(_)) => bail!("Wallet does not use a keyfile"),
+            _ => Ok(()),
+        }
+    }
+
+    fn decrypt_with_key(&self, encryption_key: &AesKey) -> Result<Keypair> {
+        let aead = Aes256Gcm::new(GenericArray::from_slice(encryption_key));
+        let mut buffer = Zeroizing::new(self.encrypted.to_owned());
+        let associated_data = Self::associated_data(&self.public_key, &self.metadata)?;
         match aead.decrypt_in_place_detached(
             self.iv.as_ref().into(),
-            &self.public_key.to_bytes(),
+            &associated_data,
             &mut buffer,
             self.tag.as_ref().into(),
         ) {
             Err(_) => Err(anyhow!("Failed to decrypt wallet")),
             _ => Ok(()),
         }?;
-        let keypair = Keypair::read(&mut Cursor::new(buffer))?;
+        let keypair = Keypair::read(&mut Cursor::new(&buffer[..]))?;
         Ok(keypair)
     }
 
+    /// The AES-GCM associated data of a wallet. The public key and, for V3
+    /// wallets, the metadata block are authenticated with the encrypted key.
+    fn associated_data(public_key: &PublicKey, metadata: &Option<Metadata>) -> Result<Vec<u8>> {
+        let mut associated_data = public_key.to_bytes();
+        if let Some(metadata) = metadata {
+            associated_data.extend_from_slice(&metadata.to_bytes()?);
+        }
+        Ok(associated_data)
+    }
+
+    /// Tries every set of K key shares of a sharded wallet until one
+    /// decrypts the wallet. This allows a wallet to be decrypted even when
+    /// more than K shards are given and some of them are corrupt. Returns the
+    /// decrypted keypair and the indices of the key shares that were used,
+    /// or None if no set of key shares decrypts the wallet.
+    fn find_key_shares(
+        &self,
+        format: &format::Sharded,
+        stretched_key: &AesKey,
+    ) -> Result<Option<(Keypair, Vec<usize>)>> {
+        if format.key_shares.len() < format.recovery_threshold as usize {
+            bail!("not enouth keyshares to recover key");
+        }
+        for subset in format.key_share_subsets() {
+            if let Ok(keypair) = self.decrypt_with_key_shares(format, stretched_key, &subset) {
+                return Ok(Some((keypair, subset)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn decrypt_with_key_shares(
+        &self,
+        format: &format::Sharded,
+        stretched_key: &AesKey,
+        subset: &[usize],
+    ) -> Result<Keypair> {
+        let key_shares: Vec<&format::KeyShare> =
+            subset.iter().map(|i| &format.key_shares[*i]).collect();
+        let mut encryption_key = Zeroizing::new(*stretched_key);
+        format::Sharded::combine_key(&key_shares, &mut *encryption_key)?;
+        self.decrypt_with_key(&encryption_key)
+    }
+
+    /// Checks each key share of a sharded wallet. A set of K key shares that
+    /// decrypts the wallet is looked for first, and every other key share is
+    /// then checked by swapping it into that set. If no set of key shares
+    /// decrypts the wallet the status of all shares is unknown.
+    pub fn check_shards(
+        &self,
+        password: &[u8],
+        keyfile: Option<&Keyfile>,
+    ) -> Result<Vec<ShardStatus>> {
+        self.check_keyfile(keyfile)?;
+        let format = self.sharded_format()?;
+        let share_count = format.key_shares.len();
+        let threshold = format.recovery_threshold as usize;
+        if share_count < threshold {
+            return Ok(vec![ShardStatus::Unknown; share_count]);
+        }
+        let mut stretched_key = Zeroizing::new(AesKey::default());
+        format::stretch_key(&format.pwhash, password, keyfile, &mut *stretched_key)?;
+        let good_subset = match self.find_key_shares(format, &stretched_key)? {
+            Some((_, subset)) => subset,
+            None => return Ok(vec![ShardStatus::Unknown; share_count]),
+        };
+        let mut statuses = vec![ShardStatus::Corrupt; share_count];
+        for (index, status) in statuses.iter_mut().enumerate() {
+            if good_subset.contains(&index) {
+                *status = ShardStatus::Good;
+                continue;
+            }
+            let mut subset = good_subset[..threshold - 1].to_vec();
+            subset.push(index);
+            if self
+                .decrypt_with_key_shares(format, &stretched_key, &subset)
+                .is_ok()
+            {
+                *status = ShardStatus::Good;
+            }
+        }
+        Ok(statuses)
+    }
+
     pub fn address(&self) -> Result<String> {
         Ok(self.public_key.to_string())
     }
@@ -100,7 +265,7 @@ impl Wallet {
         }
     }
 
-    fn sharded_format(&self) -> Result<&format::Sharded> {
+    pub fn sharded_format(&self) -> Result<&format::Sharded> {
         match &self.format {
             Format::Sharded(format) => Ok(format),
             _ => Err(anyhow!("Wallet not sharded")),
@@ -111,6 +276,20 @@ impl Wallet {
         self.sharded_format().is_ok()
     }
 
+    pub fn mut_slots_format(&mut self) -> Result<&mut format::Slots> {
+        match &mut self.format {
+            Format::Slots(format) => Ok(format),
+            _ => Err(anyhow!("Wallet has no key slots")),
+        }
+    }
+
+    pub fn slots_format(&self) -> Result<&format::Slots> {
+        match &self.format {
+            Format::Slots(format) => Ok(format),
+            _ => Err(anyhow!("Wallet has no key slots")),
+        }
+    }
+
     pub fn shards(&self) -> Result<Vec<Wallet>> {
         let format = self.sharded_format()?;
         let mut wallets = vec![];
@@ -119,6 +298,7 @@ impl Wallet {
                 format: Format::Sharded(shard),
                 encrypted: self.encrypted.clone(),
                 public_key: self.public_key.clone(),
+                metadata: self.metadata.clone(),
                 ..*self
             })
         }
@@ -126,35 +306,53 @@ impl Wallet {
     }
 
     pub fn absorb_shard(&mut self, shard: &Wallet) -> Result {
+        if self.public_key != shard.public_key {
+            bail!("Shard belongs to a different wallet");
+        }
+        if self.keyfile != shard.keyfile {
+            bail!("Shard keyfile use does not match");
+        }
+        if self.metadata != shard.metadata {
+            bail!("Shard metadata does not match");
+        }
         let format = self.mut_sharded_format()?;
         let other_format = shard.sharded_format()?;
 
         format.absorb(other_format)
     }
 
-    fn read_pwhash(reader: &mut dyn io::Read) -> Result<PwHash> {
-        let kind = reader.read_u8()?;
-        match kind {
-            PWHASH_KIND_PBKDF2 => Ok(PwHash::pbkdf2_default()),
-            PWHASH_KIND_ARGON2ID13 => Ok(PwHash::argon2id13_default()),
-            _ => Err(anyhow!("Invalid pwhash kind {}", kind)),
-        }
-    }
-
     pub fn read(reader: &mut dyn io::Read) -> Result<Wallet> {
         let kind = reader.read_u16::<LittleEndian>()?;
+        let keyfile = kind & WALLET_KIND_KEYFILE != 0;
+        let kind = kind & !WALLET_KIND_KEYFILE;
         let mut format = match kind {
             WALLET_KIND_BASIC_V1 => Format::basic(PwHash::pbkdf2_default()),
-            WALLET_KIND_BASIC_V2 => Format::basic(Self::read_pwhash(reader)?),
+            WALLET_KIND_BASIC_V2 | WALLET_KIND_BASIC_V3 => {
+                Format::basic(PwHash::read_kind(reader)?)
+            }
             WALLET_KIND_SHARDED_V1 => Format::sharded_default(PwHash::pbkdf2_default()),
-            WALLET_KIND_SHARDED_V2 => Format::sharded_default(Self::read_pwhash(reader)?),
+            WALLET_KIND_SHARDED_V2 | WALLET_KIND_SHARDED_V3 => {
+                Format::sharded_default(PwHash::read_kind(reader)?)
+            }
+            // Each key slot stores its own password hash
+            WALLET_KIND_SLOTS_V2 | WALLET_KIND_SLOTS_V3 => {
+                Format::slots(PwHash::argon2id13_default())
+            }
             _ => bail!("Invalid wallet kind {}", kind),
         };
         format.read(reader)?;
         let public_key = PublicKey::read(reader)?;
+        let metadata = match kind {
+            WALLET_KIND_BASIC_V3 | WALLET_KIND_SHARDED_V3 | WALLET_KIND_SLOTS_V3 => {
+                Some(Metadata::read(reader)?)
+            }
+            _ => None,
+        };
         let mut iv = Iv::default();
         reader.read_exact(&mut iv)?;
-        format.mut_pwhash().read(reader)?;
+        if !matches!(format, Format::Slots(_)) {
+            format.mut_pwhash().read(reader)?;
+        }
         let mut tag = Tag::default();
         reader.read_exact(&mut tag)?;
         let mut encrypted = vec![];
@@ -166,28 +364,41 @@ impl Wallet {
             tag,
             encrypted,
             format,
+            metadata,
+            keyfile,
         })
     }
 
-    fn write_pwhash(pwhash: &PwHash, writer: &mut dyn io::Write) -> Result {
-        match pwhash {
-            PwHash::Pbkdf2(_) => writer.write_u8(PWHASH_KIND_PBKDF2)?,
-            PwHash::Argon2id13(_) => writer.write_u8(PWHASH_KIND_ARGON2ID13)?,
-        }
-        Ok(())
-    }
-
     pub fn write(&self, writer: &mut dyn io::Write) -> Result {
-        let kind = match self.format {
-            Format::Basic(_) => WALLET_KIND_BASIC_V2,
-            Format::Sharded(_) => WALLET_KIND_SHARDED_V2,
+        // Wallets without metadata are still written as V2 so older releases
+        // can read them
+        let kind = match (&self.format, &self.metadata) {
+            (Format::Basic(_), None) => WALLET_KIND_BASIC_V2,
+            (Format::Basic(_), Some(_)) => WALLET_KIND_BASIC_V3,
+            (Format::Sharded(_), None) => WALLET_KIND_SHARDED_V2,
+            (Format::Sharded(_), Some(_)) => WALLET_KIND_SHARDED_V3,
+            (Format::Slots(_), None) => WALLET_KIND_SLOTS_V2,
+            (Format::Slots(_), Some(_)) => WALLET_KIND_SLOTS_V3,
+        };
+        let kind = if self.keyfile {
+            kind | WALLET_KIND_KEYFILE
+        } else {
+            kind
         };
         writer.write_u16::<LittleEndian>(kind)?;
-        Self::write_pwhash(self.format.pwhash(), writer)?;
+        let slots = matches!(self.format, Format::Slots(_));
+        if !slots {
+            self.format.pwhash().write_kind(writer)?;
+        }
         self.format.write(writer)?;
         self.public_key.write(writer)?;
+        if let Some(metadata) = &self.metadata {
+            metadata.write(writer)?;
+        }
         writer.write_all(&self.iv)?;
-        self.format.pwhash().write(writer)?;
+        if !slots {
+            self.format.pwhash().write(writer)?;
+        }
         writer.write_all(&self.tag)?;
         writer.write_all(&self.encrypted)?;
         Ok(())
@@ -209,9 +420,9 @@ mod tests {
             pwhash: PwHash::argon2id13_default(),
         };
         let password = b"passsword";
-        let wallet = Wallet::encrypt(&from_keypair, password, Format::Basic(format))
+        let wallet = Wallet::encrypt(&from_keypair, password, None, Format::Basic(format), None)
             .expect("wallet creation");
-        let to_keypair = wallet.decrypt(password).expect("wallet to keypair");
+        let to_keypair = wallet.decrypt(password, None).expect("wallet to keypair");
         assert_eq!(from_keypair, to_keypair);
     }
 
@@ -225,9 +436,132 @@ mod tests {
             key_shares: vec![],
         };
         let password = b"passsword";
-        let wallet = Wallet::encrypt(&from_keypair, password, Format::Sharded(format))
+        let wallet = Wallet::encrypt(&from_keypair, password, None, Format::Sharded(format), None)
+            .expect("wallet creation");
+        let to_keypair = wallet.decrypt(password, None).expect("wallet to keypair");
+        assert_eq!(from_keypair, to_keypair);
+    }
+
+    #[test]
+    fn corrupt_shard() {
+        let from_keypair = Keypair::default();
+        let format = Format::sharded(5, 3, PwHash::argon2id13_default());
+        let password = b"passsword";
+        let mut wallet =
+            Wallet::encrypt(&from_keypair, password, None, format, None).expect("wallet creation");
+        match &mut wallet.format {
+            Format::Sharded(format) => format.key_shares[1].0[7] ^= 0xff,
+            _ => panic!("sharded wallet expected"),
+        }
+        let to_keypair = wallet.decrypt(password, None).expect("wallet to keypair");
+        assert_eq!(from_keypair, to_keypair);
+
+        let statuses = wallet.check_shards(password, None).expect("shard statuses");
+        assert_eq!(
+            vec![
+                ShardStatus::Good,
+                ShardStatus::Corrupt,
+                ShardStatus::Good,
+                ShardStatus::Good,
+                ShardStatus::Good
+            ],
+            statuses
+        );
+    }
+
+    #[test]
+    fn authenticated_metadata() {
+        let from_keypair = Keypair::default();
+        let format = Format::basic(PwHash::pbkdf2(1000));
+        let metadata = Metadata {
+            label: Some("payments".to_string()),
+            ..Metadata::now()
+        };
+        let password = b"passsword";
+        let wallet = Wallet::encrypt(
+            &from_keypair,
+            password,
+            None,
+            format,
+            Some(metadata.clone()),
+        )
+        .expect("wallet creation");
+        let mut buffer = vec![];
+        wallet.write(&mut buffer).expect("wallet write");
+        assert_eq!(
+            WALLET_KIND_BASIC_V3,
+            u16::from_le_bytes([buffer[0], buffer[1]])
+        );
+
+        let mut wallet = Wallet::read(&mut Cursor::new(buffer)).expect("wallet read");
+        assert_eq!(Some(metadata), wallet.metadata);
+        let to_keypair = wallet.decrypt(password, None).expect("wallet to keypair");
+        assert_eq!(from_keypair, to_keypair);
+
+        if let Some(metadata) = &mut wallet.metadata {
+            metadata.label = Some("savings".to_string());
+        }
+        assert!(wallet.decrypt(password, None).is_err());
+    }
+
+    #[test]
+    fn keyfile() {
+        let from_keypair = Keypair::default();
+        let format = Format::basic(PwHash::pbkdf2(1000));
+        let keyfile = Keyfile::new(b"something held".to_vec()).expect("keyfile");
+        let password = b"passsword";
+        let wallet = Wallet::encrypt(&from_keypair, password, Some(&keyfile), format, None)
             .expect("wallet creation");
-        let to_keypair = wallet.decrypt(password).expect("wallet to keypair");
+        let mut buffer = vec![];
+        wallet.write(&mut buffer).expect("wallet write");
+        assert_eq!(
+            WALLET_KIND_BASIC_V2 | WALLET_KIND_KEYFILE,
+            u16::from_le_bytes([buffer[0], buffer[1]])
+        );
+
+        let wallet = Wallet::read(&mut Cursor::new(buffer)).expect("wallet read");
+        assert!(wallet.keyfile);
+        let to_keypair = wallet
+            .decrypt(password, Some(&keyfile))
+            .expect("wallet to keypair");
         assert_eq!(from_keypair, to_keypair);
+        assert!(wallet.decrypt(password, None).is_err());
+        let other_keyfile = Keyfile::new(b"something else".to_vec()).expect("keyfile");
+        assert!(wallet.decrypt(password, Some(&other_keyfile)).is_err());
+    }
+
+    #[test]
+    fn key_slots() {
+        let from_keypair = Keypair::default();
+        let format = Format::slots(PwHash::pbkdf2(1000));
+        let mut wallet =
+            Wallet::encrypt(&from_keypair, b"first", None, format, None).expect("wallet creation");
+        let format = wallet.mut_slots_format().expect("slots format");
+        let (index, data_key) = format.unseal(b"first", None).expect("unseal");
+        assert_eq!(0, index);
+        let slot =
+            format::KeySlot::seal(&data_key, b"second", None, PwHash::pbkdf2(1000)).expect("seal");
+        format.key_slots.push(slot);
+
+        let mut buffer = vec![];
+        wallet.write(&mut buffer).expect("wallet write");
+        assert_eq!(
+            WALLET_KIND_SLOTS_V2,
+            u16::from_le_bytes([buffer[0], buffer[1]])
+        );
+        let mut wallet = Wallet::read(&mut Cursor::new(buffer)).expect("wallet read");
+        for password in &[&b"first"[..], &b"second"[..]] {
+            let to_keypair = wallet.decrypt(password, None).expect("wallet to keypair");
+            assert_eq!(from_keypair, to_keypair);
+        }
+        assert!(wallet.decrypt(b"third", None).is_err());
+
+        wallet
+            .mut_slots_format()
+            .expect("slots format")
+            .key_slots
+            .remove(0);
+        assert!(wallet.decrypt(b"first", None).is_err());
+        assert!(wallet.decrypt(b"second", None).is_ok());
     }
 }